@@ -0,0 +1,127 @@
+use crate::input::InputError;
+use crate::output::Format;
+use crate::solver::Solver;
+use std::fmt;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Stage {
+    Parse,
+    Part1,
+    Part2,
+}
+
+impl fmt::Display for Stage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Stage::Parse => write!(f, "parse"),
+            Stage::Part1 => write!(f, "part1"),
+            Stage::Part2 => write!(f, "part2"),
+        }
+    }
+}
+
+// wall time statistics of a single stage over all iterations
+#[derive(Debug)]
+pub struct Timing {
+    pub stage: Stage,
+    pub iterations: usize,
+    pub min: Duration,
+    pub median: Duration,
+    pub max: Duration,
+}
+
+impl Timing {
+    fn from_samples(stage: Stage, mut samples: Vec<Duration>) -> Timing {
+        samples.sort();
+        return Timing {
+            stage: stage,
+            iterations: samples.len(),
+            min: samples.first().copied().unwrap_or_default(),
+            median: samples.get(samples.len() / 2).copied().unwrap_or_default(),
+            max: samples.last().copied().unwrap_or_default(),
+        };
+    }
+}
+
+// Runs parse, part 1 and part 2 of a solver `iterations` times and collects
+// the timings of each stage. Every iteration parses the input again.
+pub fn bench<S: Solver>(input: &str, iterations: usize) -> Result<Vec<Timing>, InputError> {
+    let mut parse = Vec::new();
+    let mut part1 = Vec::new();
+    let mut part2 = Vec::new();
+
+    for _ in 0..iterations {
+        let start = Instant::now();
+        let parsed = S::parse(black_box(input))?;
+        parse.push(start.elapsed());
+
+        let start = Instant::now();
+        black_box(S::part1(&parsed));
+        part1.push(start.elapsed());
+
+        let start = Instant::now();
+        black_box(S::part2(&parsed));
+        part2.push(start.elapsed());
+    }
+
+    return Ok(vec![
+        Timing::from_samples(Stage::Parse, parse),
+        Timing::from_samples(Stage::Part1, part1),
+        Timing::from_samples(Stage::Part2, part2),
+    ]);
+}
+
+pub fn render_timings(timings: &[(u32, Vec<Timing>)], format: Format) -> String {
+    let mut out = String::new();
+    match format {
+        Format::Text => {
+            for (day, day_timings) in timings {
+                for timing in day_timings {
+                    out.push_str(&format!(
+                        "Day {:>2} {}: min {:>10.3?}  median {:>10.3?}  max {:>10.3?}  ({} runs)\n",
+                        day, timing.stage, timing.min, timing.median, timing.max, timing.iterations));
+                }
+            }
+        }
+        Format::Json => {
+            let mut entries = Vec::new();
+            for (day, day_timings) in timings {
+                for timing in day_timings {
+                    entries.push(format!(
+                        "  {{\"day\": {}, \"stage\": \"{}\", \"iterations\": {}, \"min_ns\": {}, \"median_ns\": {}, \"max_ns\": {}}}",
+                        day, timing.stage, timing.iterations,
+                        timing.min.as_nanos(), timing.median.as_nanos(), timing.max.as_nanos()));
+                }
+            }
+            out.push_str(&format!("[\n{}\n]\n", entries.join(",\n")));
+        }
+        Format::Csv => {
+            out.push_str("day,stage,iterations,min_ns,median_ns,max_ns\n");
+            for (day, day_timings) in timings {
+                for timing in day_timings {
+                    out.push_str(&format!("{},{},{},{},{},{}\n",
+                        day, timing.stage, timing.iterations,
+                        timing.min.as_nanos(), timing.median.as_nanos(), timing.max.as_nanos()));
+                }
+            }
+        }
+    }
+    return out;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn statistics_of_samples() {
+        let samples = vec![5, 1, 3, 2, 4].into_iter().map(Duration::from_millis).collect();
+        let timing = Timing::from_samples(Stage::Part1, samples);
+        assert_eq!(timing.iterations, 5);
+        assert_eq!(timing.min, Duration::from_millis(1));
+        assert_eq!(timing.median, Duration::from_millis(3));
+        assert_eq!(timing.max, Duration::from_millis(5));
+    }
+}
@@ -1,48 +1,246 @@
+use crate::input::{self, InputError, Source};
+use crate::ksum::{self, Reuse};
+use crate::output::{csv_field, json_string, Answer, Format};
+use crate::solver::Solver;
+use std::fmt;
 
-use crate::input;
+pub const TARGET: i64 = 2020;
+pub const COUNT: usize = 2;
 
-fn is2020_by2(a: i32, b: i32) -> bool {
-    return a + b == 2020;
+// Most combinations of count / 2 rounded up entries that the search may
+// build, enough for 6 of the 200 puzzle entries
+const MAX_HALF_COMBINATIONS: u128 = 10_000_000;
+
+// limbs of a product hold 9 decimal digits each
+const BASE: u128 = 1_000_000_000;
+
+// Exact product of any number of entries
+#[derive(Debug, Clone, PartialEq)]
+pub struct Product {
+    negative: bool,
+    // least significant limb first
+    limbs: Vec<u32>,
 }
 
-fn is2020_by3(a: i32, b: i32, c: i32) -> bool {
-    return a + b + c == 2020;
+impl Product {
+    pub fn of(values: &[i64]) -> Product {
+        let mut limbs = vec![1];
+        let mut negative = false;
+        for value in values {
+            negative ^= *value < 0;
+            let factor = value.unsigned_abs() as u128;
+            let mut carry = 0;
+            for limb in limbs.iter_mut() {
+                let x = *limb as u128 * factor + carry;
+                *limb = (x % BASE) as u32;
+                carry = x / BASE;
+            }
+            while carry > 0 {
+                limbs.push((carry % BASE) as u32);
+                carry /= BASE;
+            }
+        }
+        while limbs.len() > 1 && limbs[limbs.len() - 1] == 0 {
+            limbs.pop();
+        }
+        return Product {
+            negative: negative && limbs != [0],
+            limbs: limbs,
+        };
+    }
+
+    // None if the product doesn't fit into an i128
+    pub fn to_i128(&self) -> Option<i128> {
+        let mut value: i128 = 0;
+        for limb in self.limbs.iter().rev() {
+            value = value.checked_mul(BASE as i128)?.checked_add(*limb as i128)?;
+        }
+        if self.negative {
+            return Some(-value);
+        }
+        return Some(value);
+    }
 }
 
- 
-pub fn question1() -> String {
-    let input_file = "input/day1.txt";
-    let input = input::lines_as::<i32>(input_file);
+impl fmt::Display for Product {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.negative {
+            write!(f, "-")?;
+        }
+        let mut limbs = self.limbs.iter().rev();
+        if let Some(first) = limbs.next() {
+            write!(f, "{}", first)?;
+        }
+        for limb in limbs {
+            write!(f, "{:09}", limb)?;
+        }
+        return Ok(());
+    }
+}
 
-    let length = input.len();
-    for i in 0..length {
-        for j in (i+1)..length {
-            let a = input[i];
-            let b = input[j];
-            if is2020_by2(a, b) {
-                return format!("Day 1.1: {} * {} = {}", a, b, a*b);
-            }
+// products too large for a number are answered as text
+impl From<Product> for Answer {
+    fn from(product: Product) -> Answer {
+        match product.to_i128() {
+            Some(value) => Answer::Signed(value),
+            None => Answer::Text(product.to_string()),
         }
     }
-    return "No solution found".to_string();
 }
 
-pub fn question2() -> String {
-    let input_file = "input/day1.txt";
-    let input = input::lines_as::<i32>(input_file);
+// Entry of the expense report, with its line number in the input
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Entry {
+    pub line: usize,
+    pub value: i64,
+}
 
-    let length = input.len();
-    for i in 0..length {
-        for j in (i+1)..length {
-            for k in (j+1)..length {
-                let a = input[i];
-                let b = input[j];
-                let c = input[k];
-                if is2020_by3(a, b, c) {
-                    return format!("Day 1.2: {} * {} * {} = {}", a, b, c, a*b*c);
+// the first `count` distinct entries that sum up to the target
+pub fn find_entries(input: &[i64], target: i64, count: usize) -> Option<Vec<Entry>> {
+    let indices = ksum::find_first_k_sum(input, count, target, Reuse::Forbidden)?;
+    let entries = indices.iter()
+        .map(|index| Entry { line: index + 1, value: input[*index] })
+        .collect();
+    return Some(entries);
+}
+
+fn product_of(entries: &[Entry]) -> Product {
+    let values: Vec<i64> = entries.iter().map(|entry| entry.value).collect();
+    return Product::of(&values);
+}
+
+// number of ways to choose k of n entries, or None if there are more than `limit`
+fn choose(n: usize, k: usize, limit: u128) -> Option<u128> {
+    let mut ways: u128 = 1;
+    for i in 0..k.min(n) {
+        // exact, the product of i + 1 consecutive numbers is divisible by (i + 1)!
+        ways = ways * (n - i) as u128 / (i + 1) as u128;
+        if ways > limit {
+            return None;
+        }
+    }
+    return Some(if k > n { 0 } else { ways });
+}
+
+// Lists the entries that reconcile to the given total, used for the
+// `--target` and `--count` options
+pub fn reconcile(source: &Source, target: i64, count: usize, format: Format) -> Result<String, String> {
+    let input = source.read().map_err(|e| e.to_string())?;
+    let values = Day1::parse(&input).map_err(|e| e.in_file(source.name()).to_string())?;
+    if count == 0 {
+        return Err(String::from("the count has to be at least 1"));
+    }
+    if choose(values.len(), count - count / 2, MAX_HALF_COMBINATIONS).is_none() {
+        return Err(format!("searching {} of {} entries takes too long", count, values.len()));
+    }
+    let entries = find_entries(&values, target, count);
+
+    let out = match (format, entries) {
+        (Format::Text, None) => format!("no {} entries sum up to {}\n", count, target),
+        (Format::Text, Some(entries)) => {
+            let terms: Vec<String> = entries.iter()
+                .map(|entry| format!("{} (line {})", entry.value, entry.line))
+                .collect();
+            format!("{} = {}\nproduct: {}\n", terms.join(" + "), target, product_of(&entries))
+        }
+        (Format::Json, entries) => {
+            let (entries, product) = match entries {
+                Some(entries) => {
+                    let json: Vec<String> = entries.iter()
+                        .map(|entry| format!("{{\"line\": {}, \"value\": {}}}", entry.line, entry.value))
+                        .collect();
+                    let product = match Answer::from(product_of(&entries)) {
+                        Answer::Text(product) => json_string(&product),
+                        product => product.to_string(),
+                    };
+                    (format!("[{}]", json.join(", ")), product)
                 }
+                None => (String::from("null"), String::from("null")),
+            };
+            format!("{{\"target\": {}, \"count\": {}, \"entries\": {}, \"product\": {}}}\n", target, count, entries, product)
+        }
+        (Format::Csv, entries) => {
+            // every row repeats the product of the match
+            let entries = entries.unwrap_or_default();
+            let product = product_of(&entries).to_string();
+            let mut out = String::from("line,value,product\n");
+            for entry in entries {
+                out.push_str(&format!("{},{},{}\n", entry.line, entry.value, csv_field(&product)));
             }
+            out
         }
+    };
+    return Ok(out);
+}
+
+pub struct Day1;
+
+impl Solver for Day1 {
+    type Input = Vec<i64>;
+    type Answer1 = Product;
+    type Answer2 = Product;
+
+    fn parse(input: &str) -> Result<Vec<i64>, InputError> {
+        return input::lines_as::<i64>(input);
+    }
+
+    fn part1(input: &Vec<i64>) -> Option<Product> {
+        return find_entries(input, TARGET, 2).map(|entries| product_of(&entries));
+    }
+
+    fn part2(input: &Vec<i64>) -> Option<Product> {
+        return find_entries(input, TARGET, 3).map(|entries| product_of(&entries));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "1721\n979\n366\n299\n675\n1456";
+
+    #[test]
+    fn example() {
+        let input = Day1::parse(EXAMPLE).unwrap();
+        assert_eq!(Day1::part1(&input).map(Answer::from), Some(Answer::Signed(514579)));
+        assert_eq!(Day1::part2(&input).map(Answer::from), Some(Answer::Signed(241861950)));
+
+        let lines: Vec<usize> = find_entries(&input, TARGET, 2).unwrap().iter().map(|entry| entry.line).collect();
+        assert_eq!(lines, vec![1, 4]);
+    }
+
+    #[test]
+    fn exact_products() {
+        assert_eq!(Product::of(&[i64::MAX, i64::MAX, 4]).to_i128(), None);
+        assert_eq!(Product::of(&[i64::MAX, i64::MAX, -2]).to_string(), "-170141183460469231694793815568465002498");
+        assert_eq!(Product::of(&[-3, 0]).to_string(), "0");
+        assert_eq!(Product::of(&[]).to_i128(), Some(1));
+        assert_eq!(Answer::from(Product::of(&[1_000_000_000, -7])), Answer::Signed(-7_000_000_000));
     }
-    return "No solution found".to_string();
-}
\ No newline at end of file
+
+    #[test]
+    fn reconcile_totals() {
+        let example = Source::inline("example", EXAMPLE);
+        assert_eq!(reconcile(&example, 2020, 2, Format::Text).unwrap(), "1721 (line 1) + 299 (line 4) = 2020\nproduct: 514579\n");
+        assert_eq!(reconcile(&example, 2, 2, Format::Text).unwrap(), "no 2 entries sum up to 2\n");
+        assert_eq!(reconcile(&example, 1345, 2, Format::Json).unwrap(),
+            "{\"target\": 1345, \"count\": 2, \"entries\": [{\"line\": 2, \"value\": 979}, {\"line\": 3, \"value\": 366}], \"product\": 358314}\n");
+        assert_eq!(reconcile(&example, 2020, 3, Format::Csv).unwrap(), "line,value,product\n2,979,241861950\n3,366,241861950\n5,675,241861950\n");
+        assert_eq!(reconcile(&example, 2, 2, Format::Csv).unwrap(), "line,value,product\n");
+        assert_eq!(reconcile(&example, 0, 7, Format::Text).unwrap(), "no 7 entries sum up to 0\n");
+    }
+
+    #[test]
+    fn count_limits() {
+        let example = Source::inline("example", EXAMPLE);
+        assert_eq!(reconcile(&example, 0, 0, Format::Text).unwrap_err(), "the count has to be at least 1");
+        assert_eq!(choose(200, 3, MAX_HALF_COMBINATIONS), Some(1_313_400));
+        assert_eq!(choose(200, 4, MAX_HALF_COMBINATIONS), None);
+        assert_eq!(choose(3, 5, MAX_HALF_COMBINATIONS), Some(0));
+
+        let input: String = (1..=200).map(|value| format!("{}\n", value)).collect();
+        let large = Source::inline("large", &input);
+        assert!(reconcile(&large, 21, 6, Format::Text).is_ok());
+        assert_eq!(reconcile(&large, 28, 7, Format::Text).unwrap_err(), "searching 7 of 200 entries takes too long");
+    }
+}
@@ -1,29 +1,9 @@
-use crate::input;
+use crate::input::{self, InputError};
+use crate::solver::Solver;
 use std::collections::HashMap;
 
 
-pub fn question1() -> String {
-    let filename = "input/day10.txt";
-    let mut input = input::lines_as::<u32>(filename);
-    let mut jolts: HashMap<u32, u32> = HashMap::new();
-
-    input.sort();
-   
-    let mut prev = 0;
-    for plug in input {
-        let diff = plug - prev;
-        prev = plug;
-        *jolts.entry(diff).or_insert(0) += 1;
-    }
-
-    // add a 3 jolt diff for the last adaptor to the device
-    *jolts.entry(3).or_insert(0) += 1;
-
-    let solution = jolts[&1] * jolts[&3];
-    return format!("Day 10.1: {} (1 jolt) * {} (3 jolt) = {}", jolts[&1], jolts[&3], solution);
-}
-
-pub fn prev_neighbors(idx: usize, input: &Vec<u32>) -> &[u32] {
+pub fn prev_neighbors(idx: usize, input: &[u32]) -> &[u32] {
     let val = input[idx];
     let mut lower_bound = idx;
     while lower_bound > 0 && (val - input[lower_bound-1]) <=3 {
@@ -32,32 +12,83 @@ pub fn prev_neighbors(idx: usize, input: &Vec<u32>) -> &[u32] {
     return &input[lower_bound..idx];
 }
 
-// using som form of dynamic programming:
-// in the sequence of adapters
-// 0|1,2,3,4,7|10
-// 1 can be reached by 0, so number of possibilites = 1
-// 2 can be reached by 0 and 1, so pos(2) = pos(1) + 1 = 2
-// 3 can be reached by 0, 1 and 2, so pos(3) = pos(2) + pos(1) + 1 = 2 + 1 + 1 = 4
-// 4 can be reached by 1, 2 and 3, so pos(4) = pos(3) + pos(2) + pos(1) = 4 + 2 + 1 = 7
-// 7 can be reached by 4, so pos(7) = pos(4) = 7
-// 10 can be reached by 7, so pos(10) = pos(7) = 7
-// => 7 total possibilites
-pub fn question2() -> String {
-    let filename = "input/day10.txt";
-    let mut input = input::lines_as::<u32>(filename);
-    input.sort();
-    input.insert(0, 0);
-
-    let mut combinations: HashMap<u32, u64> = HashMap::new();
-    combinations.insert(0, 1);
-
-    for idx in 1..input.len() {
-        let adapter = input[idx];
-        let neighbors = prev_neighbors(idx, &input);
-        let sum = neighbors.iter().map(|i| combinations[i]).sum();
-        combinations.insert(adapter, sum);
+pub struct Day10;
+
+impl Solver for Day10 {
+    type Input = Vec<u32>;
+    type Answer1 = u32;
+    type Answer2 = u64;
+
+    fn parse(input: &str) -> Result<Vec<u32>, InputError> {
+        let mut adapters = input::lines_as::<u32>(input)?;
+        adapters.sort();
+        return Ok(adapters);
+    }
+
+    fn part1(adapters: &Vec<u32>) -> Option<u32> {
+        let mut jolts: HashMap<u32, u32> = HashMap::new();
+
+        let mut prev = 0;
+        for plug in adapters {
+            let diff = plug - prev;
+            prev = *plug;
+            *jolts.entry(diff).or_insert(0) += 1;
+        }
+
+        // add a 3 jolt diff for the last adaptor to the device
+        *jolts.entry(3).or_insert(0) += 1;
+
+        let one_jolt = jolts.get(&1).copied().unwrap_or(0);
+        let three_jolt = jolts.get(&3).copied().unwrap_or(0);
+        return Some(one_jolt * three_jolt);
+    }
+
+    // using som form of dynamic programming:
+    // in the sequence of adapters
+    // 0|1,2,3,4,7|10
+    // 1 can be reached by 0, so number of possibilites = 1
+    // 2 can be reached by 0 and 1, so pos(2) = pos(1) + 1 = 2
+    // 3 can be reached by 0, 1 and 2, so pos(3) = pos(2) + pos(1) + 1 = 2 + 1 + 1 = 4
+    // 4 can be reached by 1, 2 and 3, so pos(4) = pos(3) + pos(2) + pos(1) = 4 + 2 + 1 = 7
+    // 7 can be reached by 4, so pos(7) = pos(4) = 7
+    // 10 can be reached by 7, so pos(10) = pos(7) = 7
+    // => 7 total possibilites
+    fn part2(adapters: &Vec<u32>) -> Option<u64> {
+        let mut input = adapters.clone();
+        input.insert(0, 0);
+
+        let mut combinations: HashMap<u32, u64> = HashMap::new();
+        combinations.insert(0, 1);
+
+        for idx in 1..input.len() {
+            let adapter = input[idx];
+            let neighbors = prev_neighbors(idx, &input);
+            let sum = neighbors.iter().map(|i| combinations[i]).sum();
+            combinations.insert(adapter, sum);
+        }
+
+        return input.last().map(|last| combinations[last]);
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "16\n10\n15\n5\n1\n11\n7\n19\n6\n12\n4";
 
-    let num_combinations = combinations[input.last().unwrap()];
-    return format!("Day 10.2: number of ways to arrange adapters = {}", num_combinations);
+    const LARGER_EXAMPLE: &str = "\
+28\n33\n18\n42\n31\n14\n46\n20\n48\n47\n24\n23\n49\n45\n19\n38\n\
+39\n11\n1\n32\n25\n35\n8\n17\n7\n9\n4\n2\n34\n10\n3";
+
+    #[test]
+    fn example() {
+        let input = Day10::parse(EXAMPLE).unwrap();
+        assert_eq!(Day10::part1(&input), Some(35));
+        assert_eq!(Day10::part2(&input), Some(8));
+
+        let input = Day10::parse(LARGER_EXAMPLE).unwrap();
+        assert_eq!(Day10::part1(&input), Some(220));
+        assert_eq!(Day10::part2(&input), Some(19208));
+    }
 }
@@ -1,8 +1,11 @@
-use crate::input;
+use crate::grid::{Edge, Grid, DIRECTIONS_8};
+use crate::input::{InputError, ParseError};
+use crate::solver::Solver;
+use std::convert::TryFrom;
 use std::fmt;
 
 #[derive(PartialEq, Clone, Debug)]
-enum Space {
+pub enum Space {
     Floor,
     Empty,
     Occupied
@@ -17,110 +20,46 @@ impl fmt::Display for Space {
     }
 }
 
-struct Grid {
-    grid: Vec<Vec<Space>>,
-}
+impl TryFrom<char> for Space {
+    type Error = ParseError;
 
-impl fmt::Display for Grid {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        for row in &self.grid {
-            for col in row {
-                write!(f, "{}", col)?;
-            }
-            write!(f, "\n")?;
+    fn try_from(c: char) -> Result<Space, ParseError> {
+        match c {
+            '.' => Ok(Space::Floor),
+            'L' => Ok(Space::Empty),
+            '#' => Ok(Space::Occupied),
+            _ => Err(ParseError::new(format!("unknown input '{}'", c))),
         }
-        write!(f, "")
     }
 }
 
-impl Grid {
-    fn new(input: &Vec<String>) -> Grid {
-        let mut grid = Vec::new();
-        for line in input {
-            let mut row: Vec<Space> = Vec::new();
-            for c in line.chars() {
-                row.push(match c {
-                    '.' => Space::Floor,
-                    'L' => Space::Empty,
-                    _ => panic!("unkown input {}", c)
-                })
-            }
-            grid.push(row);
-        }
-        
-        return Grid {
-            grid: grid,
-        };
-    }
-
-    fn num_rows(&self) -> usize {
-        return self.grid.len();
-    }
-
-    fn num_cols(&self) -> usize {
-        return self.grid.first().map(|r| r.len()).unwrap_or(0);
-    }
-
-    fn at(&self, row: i32, col: i32) -> Option<&Space> {
-        if row < 0 || row < 0 {
-            return None;
+// returns if the grid changed during the step
+fn next_step(
+    grid: &mut Grid<Space>,
+    change_policy: &dyn Fn(&Space, u8) -> Space,
+    neighbor_policy: &dyn Fn(&Grid<Space>, usize, usize) -> u8) -> bool
+{
+    let mut changed = false;
+
+    let new_grid = grid.map(|(row, col), value| {
+        let num_occupied_neighbors = neighbor_policy(grid, row, col);
+        let new_value = change_policy(value, num_occupied_neighbors);
+
+        // short circut the changed value, to ensure we don't need to compare old
+        // and new grid after step
+        if value != &new_value {
+            changed = true;
         }
+        return new_value;
+    });
 
-        let row = row as usize;
-        let col = col as usize;
-        if  row < self.num_rows() && col < self.num_cols() {
-            return Some(&self.grid[row][col]);
-        }
-        return None;
-    }
-
-
-    // returns if the grid changed during the step
-    fn next_step(
-        &mut self,
-        change_policy: &dyn Fn(&Space, u8) -> Space,
-        neighbor_policy: &dyn Fn(&Grid, i32, i32) -> u8) -> bool 
-    {
-        let mut new_grid:Vec<Vec<Space>> = Vec::new();
-        let mut changed = false;
-
-        for row in 0..self.num_rows() {
-            let mut new_row: Vec<Space> = Vec::new();
-
-            for col in 0..self.num_cols() {
-                let num_occupied_neighbors = neighbor_policy(&self, row as i32, col as i32);
-                let value = self.at(row as i32 , col as i32);
-                let new_value = match value {
-                    Some(value) => change_policy(value, num_occupied_neighbors),
-                    None => panic!("out of bound: {}, {}", row, col)
-                };
-
-                // short circut the changed value, to ensure we don't need to compare old
-                // and new grid after step
-                if value != Some(&new_value) {
-                    changed = true;
-                }
-                new_row.push(new_value);
-            }
-            new_grid.push(new_row);
-        }
-
-        // set new grid
-        self.grid = new_grid;
-        return changed;
-    }
+    // set new grid
+    *grid = new_grid;
+    return changed;
+}
 
-    fn num_occupied_seats(&self) -> u32 {
-        let mut seats = 0;
-        for row in &self.grid {
-            for col in row {
-                if col == &Space::Occupied {
-                    seats +=1;
-                }
-            }
-        }
-        return seats;
-    }
+fn num_occupied_seats(grid: &Grid<Space>) -> usize {
+    return grid.cells().filter(|space| **space == Space::Occupied).count();
 }
 
 fn change_policy(space: &Space, num_occupied_neighbors: u8) -> Space {
@@ -139,76 +78,80 @@ fn change_policy2(space: &Space, num_occupied_neighbors: u8) -> Space {
     }
 }
 
-fn num_occupied_direct_neighbors(grid: &Grid, row: i32, col: i32) -> u8 {
-    let neighbors = vec![
-        (row-1, col),
-        (row-1, col+1),
-        (row,   col+1),
-        (row+1, col+1),
-        (row+1, col),
-        (row+1, col-1),
-        (row,   col-1),
-        (row-1, col-1)];
-    let mut occupied_neighbors = 0;
-
-    for (r, c) in neighbors {
-        let value = grid.at(r, c);
-        match value {
-            Some(Space::Occupied) => occupied_neighbors += 1,
-            _ => ()
-        }
-    }
-
-    return occupied_neighbors;
+fn num_occupied_direct_neighbors(grid: &Grid<Space>, row: usize, col: usize) -> u8 {
+    let occupied_neighbors = grid.neighbours_8(row, col)
+        .filter(|(_, space)| **space == Space::Occupied)
+        .count();
+    return occupied_neighbors as u8;
 }
 
-fn num_occupied_visible_neighbors(grid: &Grid, row: i32, col: i32) -> u8 {
-    let directions = vec![
-        (-1,  0),
-        (-1,  1),
-        (0,   1),
-        (1,   1),
-        (1,   0),
-        (1,  -1),
-        (0,  -1),
-        (-1, -1)];
-
+fn num_occupied_visible_neighbors(grid: &Grid<Space>, row: usize, col: usize) -> u8 {
     let mut occupied_neighbors = 0;
-    for (dx, dy) in directions {
-        let mut x = row + dx;
-        let mut y = col + dy;
-
-        while grid.at(x, y).map_or(false, |p| p == &Space::Floor) {
-            x += dx;
-            y += dy;
-        }
-        if grid.at(x, y).map_or(false, |p| p == &Space::Occupied) {
+    for (drow, dcol) in DIRECTIONS_8.iter() {
+        let first_seat = grid.ray(row, col, *drow, *dcol).find(|(_, space)| **space != Space::Floor);
+        if let Some((_, Space::Occupied)) = first_seat {
             occupied_neighbors += 1;
         }
     }
     return occupied_neighbors;
 }
 
-pub fn question1() -> String {
-    let filename = "input/day11.txt";
-    let input = input::lines_as::<String>(filename);
-    let mut grid = Grid::new(&input);
+pub struct Day11;
+
+impl Solver for Day11 {
+    type Input = Grid<Space>;
+    type Answer1 = usize;
+    type Answer2 = usize;
 
-    while grid.next_step(&change_policy, &num_occupied_direct_neighbors) {
+    fn parse(input: &str) -> Result<Grid<Space>, InputError> {
+        return Grid::parse(input, Edge::Bounded);
     }
-    let occupied_seats = grid.num_occupied_seats();
 
-    return format!("Day 11.1: occupied seats = {}", occupied_seats);
-}
+    fn part1(grid: &Grid<Space>) -> Option<usize> {
+        let mut grid = grid.clone();
 
-pub fn question2() -> String {
-    let filename = "input/day11.txt";
-    let input = input::lines_as::<String>(filename);
-    let mut grid = Grid::new(&input);
+        while next_step(&mut grid, &change_policy, &num_occupied_direct_neighbors) {
+        }
+        return Some(num_occupied_seats(&grid));
+    }
 
-    while grid.next_step(&change_policy2, &num_occupied_visible_neighbors) {
+    fn part2(grid: &Grid<Space>) -> Option<usize> {
+        let mut grid = grid.clone();
+
+        while next_step(&mut grid, &change_policy2, &num_occupied_visible_neighbors) {
+        }
+        return Some(num_occupied_seats(&grid));
     }
+}
 
-    let occupied_seats = grid.num_occupied_seats();
-    return format!("Day 11.2: occupied seats = {}", occupied_seats);
-}
\ No newline at end of file
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+L.LL.LL.LL
+LLLLLLL.LL
+L.L.L..L..
+LLLL.LL.LL
+L.LL.LL.LL
+L.LLLLL.LL
+..L.L.....
+LLLLLLLLLL
+L.LLLLLL.L
+L.LLLLL.LL";
+
+    #[test]
+    fn example() {
+        let input = Day11::parse(EXAMPLE).unwrap();
+        assert_eq!(Day11::part1(&input), Some(37));
+        assert_eq!(Day11::part2(&input), Some(26));
+    }
+    // seats left of the first column or above the first row don't exist
+    #[test]
+    fn left_and_top_edge() {
+        let grid = Day11::parse("L.#\n..#\n..#").unwrap();
+        assert_eq!(num_occupied_direct_neighbors(&grid, 0, 0), 0);
+        assert_eq!(num_occupied_visible_neighbors(&grid, 0, 0), 2);
+        assert_eq!(num_occupied_direct_neighbors(&grid, 1, 1), 3);
+    }
+}
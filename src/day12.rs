@@ -1,7 +1,5 @@
-use crate::input;
-
-#[derive(Debug)]
-enum Direction {North, East, South, West}
+use crate::input::{self, InputError, ParseError};
+use crate::solver::Solver;
 
 #[derive(Debug)]
 enum Action {
@@ -14,8 +12,8 @@ enum Action {
     Forward
 }
 impl Action {
-    fn from_string(str: &char) -> Action {
-        return match str {
+    fn from_string(str: &char) -> Result<Action, ParseError> {
+        return Ok(match str {
             'N' => Action::North,
             'E' => Action::East,
             'S' => Action::South,
@@ -23,25 +21,29 @@ impl Action {
             'L' => Action::Left,
             'R' => Action::Right,
             'F' => Action::Forward,
-            _ => panic!("Unknown action {}", str)
-        };
+            _ => return Err(ParseError::new(format!("unknown action '{}'", str)))
+        });
     }
 }
 
 
 #[derive(Debug)]
-struct Instruction {
+pub struct Instruction {
     action: Action,
     value: i32
 }
 impl Instruction {
-    fn from_string(line: &String) -> Instruction {
-        let action = Action::from_string(&line.chars().nth(0).unwrap());
-        let value = String::from(&line[1..]).parse::<i32>().unwrap();
-        Instruction {
+    fn from_string(line: &str) -> Result<Instruction, ParseError> {
+        let mut chars = line.chars();
+        let action = match chars.next() {
+            Some(c) => Action::from_string(&c)?,
+            None => return Err(ParseError::new(String::from("empty instruction")))
+        };
+        let value = chars.as_str().parse::<i32>()?;
+        Ok(Instruction {
             action: action,
             value: value
-        }
+        })
     }
 }
 
@@ -167,29 +169,47 @@ impl Ferry {
 }
 
 
-pub fn question1() -> String {
-    let filename = "input/day12.txt";
-    let instructions: Vec<Instruction> =
-        input::lines_as::<String>(filename).iter().map(Instruction::from_string).collect();
-    let mut ferry = Ferry::new(90, 0, 0);
+pub struct Day12;
 
-    for instruction in instructions {
-        ferry.follow_instruction(&instruction);
+impl Solver for Day12 {
+    type Input = Vec<Instruction>;
+    type Answer1 = i32;
+    type Answer2 = i32;
+
+    fn parse(input: &str) -> Result<Vec<Instruction>, InputError> {
+        return input::lines_with(input, Instruction::from_string);
     }
-    
-    return format!("Day 12.1: distance from origin = {}", ferry.manhattan_distance());
-}
 
-pub fn question2() -> String {
-    let filename = "input/day12.txt";
-    let instructions: Vec<Instruction> =
-        input::lines_as::<String>(filename).iter().map(Instruction::from_string).collect();
-    let waypoint = Waypoint::new(10, 1);
-    let mut ferry = Ferry::new_with_waypoint(0, 0, 0, waypoint);
+    fn part1(instructions: &Vec<Instruction>) -> Option<i32> {
+        let mut ferry = Ferry::new(90, 0, 0);
 
-    for instruction in &instructions {
-        ferry.follow_instruction_with_waypoint(instruction);
+        for instruction in instructions {
+            ferry.follow_instruction(instruction);
+        }
+        return Some(ferry.manhattan_distance());
     }
 
-    return format!("Day 12.2: distance from origin = {}", ferry.manhattan_distance());
-}
\ No newline at end of file
+    fn part2(instructions: &Vec<Instruction>) -> Option<i32> {
+        let waypoint = Waypoint::new(10, 1);
+        let mut ferry = Ferry::new_with_waypoint(0, 0, 0, waypoint);
+
+        for instruction in instructions {
+            ferry.follow_instruction_with_waypoint(instruction);
+        }
+        return Some(ferry.manhattan_distance());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "F10\nN3\nF7\nR90\nF11";
+
+    #[test]
+    fn example() {
+        let input = Day12::parse(EXAMPLE).unwrap();
+        assert_eq!(Day12::part1(&input), Some(25));
+        assert_eq!(Day12::part2(&input), Some(286));
+    }
+}
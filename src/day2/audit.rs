@@ -0,0 +1,169 @@
+use super::PasswordEntry;
+use super::policy::PasswordPolicy;
+use crate::output::{csv_field, json_string, Format};
+
+// Verdict of a single policy on an entry
+pub struct Verdict {
+    pub policy: String,
+    // what the policy looked at, e.g. the number of occurrences of the letter
+    pub observed: Option<String>,
+    // Err with the reason if the entry fails the policy
+    pub result: Result<(), String>,
+}
+
+// Audited line of the password database
+pub struct Row<'a> {
+    pub line: usize,
+    pub entry: &'a PasswordEntry,
+    pub verdicts: Vec<Verdict>,
+}
+
+pub fn audit<'a>(entries: &'a [PasswordEntry], policies: &[Box<dyn PasswordPolicy>]) -> Vec<Row<'a>> {
+    return entries.iter()
+        .enumerate()
+        .map(|(index, entry)| Row {
+            line: index + 1,
+            entry: entry,
+            verdicts: policies.iter()
+                .map(|policy| Verdict {
+                    policy: policy.name(),
+                    observed: policy.observed(entry),
+                    result: policy.check(entry),
+                })
+                .collect(),
+        })
+        .collect();
+}
+
+fn verdict_name(verdict: &Verdict) -> &'static str {
+    match verdict.result {
+        Ok(()) => "valid",
+        Err(_) => "invalid",
+    }
+}
+
+// the reasons of all failed policies, empty if the entry passes all of them
+fn reasons(row: &Row) -> String {
+    let reasons: Vec<String> = row.verdicts.iter()
+        .filter_map(|verdict| match &verdict.result {
+            Ok(()) => None,
+            Err(reason) => Some(format!("{}: {}", verdict.policy, reason)),
+        })
+        .collect();
+    return reasons.join("; ");
+}
+
+// Renders the rows as a table (text), a JSON array of lines or as CSV with
+// one row per line and policy.
+pub fn render(rows: &[Row], format: Format) -> String {
+    match format {
+        Format::Text => render_table(rows),
+        Format::Json => {
+            let lines: Vec<String> = rows.iter()
+                .map(|row| {
+                    let verdicts: Vec<String> = row.verdicts.iter()
+                        .map(|verdict| format!("{{\"policy\": {}, \"valid\": {}, \"observed\": {}, \"reason\": {}}}",
+                            json_string(&verdict.policy),
+                            verdict.result.is_ok(),
+                            verdict.observed.as_ref().map(|observed| json_string(observed)).unwrap_or_else(|| String::from("null")),
+                            verdict.result.as_ref().err().map(|reason| json_string(reason)).unwrap_or_else(|| String::from("null"))))
+                        .collect();
+                    let entry = format!("{{\"min\": {}, \"max\": {}, \"letter\": {}, \"password\": {}}}",
+                        row.entry.min, row.entry.max, json_string(&row.entry.c.to_string()), json_string(&row.entry.pw));
+                    format!("  {{\"line\": {}, \"entry\": {}, \"policies\": [{}]}}", row.line, entry, verdicts.join(", "))
+                })
+                .collect();
+            format!("[\n{}\n]\n", lines.join(",\n"))
+        }
+        Format::Csv => {
+            let mut out = String::from("line,entry,policy,valid,observed,reason\n");
+            for row in rows {
+                for verdict in &row.verdicts {
+                    out.push_str(&format!("{},{},{},{},{},{}\n", row.line, csv_field(&row.entry.to_string()), csv_field(&verdict.policy),
+                        verdict.result.is_ok(), csv_field(verdict.observed.as_deref().unwrap_or("")),
+                        csv_field(verdict.result.as_ref().err().map(String::as_str).unwrap_or(""))));
+                }
+            }
+            out
+        }
+    }
+}
+
+fn render_table(rows: &[Row]) -> String {
+    let mut cells: Vec<Vec<String>> = Vec::new();
+    let mut header = vec![String::from("line"), String::from("entry")];
+    if let Some(row) = rows.first() {
+        for verdict in &row.verdicts {
+            header.push(verdict.policy.clone());
+            header.push(String::from("observed"));
+        }
+    }
+    header.push(String::from("reason"));
+    cells.push(header);
+
+    for row in rows {
+        let mut line = vec![row.line.to_string(), row.entry.to_string()];
+        for verdict in &row.verdicts {
+            line.push(verdict_name(verdict).to_string());
+            line.push(verdict.observed.clone().unwrap_or_else(|| String::from("-")));
+        }
+        line.push(reasons(row));
+        cells.push(line);
+    }
+
+    let columns = cells[0].len();
+    let widths: Vec<usize> = (0..columns)
+        .map(|column| cells.iter().map(|line| line[column].chars().count()).max().unwrap_or(0))
+        .collect();
+
+    let mut out = String::new();
+    for line in cells {
+        let mut formatted = vec![format!("{:>width$}", line[0], width = widths[0])];
+        for column in 1..columns - 1 {
+            formatted.push(format!("{:<width$}", line[column], width = widths[column]));
+        }
+        formatted.push(line[columns - 1].clone());
+        out.push_str(formatted.join("  ").trim_end());
+        out.push('\n');
+    }
+    return out;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::Day2;
+    use super::super::policy::parse_policy;
+    use crate::solver::Solver;
+
+    fn entries() -> Vec<PasswordEntry> {
+        return Day2::parse("1-3 a: abcde\n1-3 b: cdefg\n2-9 c: ccccccccc").unwrap();
+    }
+
+    fn rows(entries: &[PasswordEntry]) -> Vec<Row<'_>> {
+        let policies = vec![parse_policy("count").unwrap(), parse_policy("positions").unwrap()];
+        return audit(entries, &policies);
+    }
+
+    #[test]
+    fn table() {
+        assert_eq!(render(&rows(&entries()), Format::Text), "\
+line  entry             count    observed  positions  observed  reason
+   1  1-3 a: abcde      valid    1         valid      a c
+   2  1-3 b: cdefg      invalid  0         invalid    c e       count: 'b' occurs 0 times, expected 1 to 3; positions: 'b' is at neither position 1 nor 3
+   3  2-9 c: ccccccccc  valid    9         invalid    c c       positions: 'c' is at both positions 2 and 9
+");
+    }
+
+    #[test]
+    fn json_and_csv() {
+        let entries = entries();
+        let json = render(&rows(&entries), Format::Json);
+        assert!(json.contains("  {\"line\": 3, \"entry\": {\"min\": 2, \"max\": 9, \"letter\": \"c\", \"password\": \"ccccccccc\"}, \"policies\": [\
+{\"policy\": \"count\", \"valid\": true, \"observed\": \"9\", \"reason\": null}, \
+{\"policy\": \"positions\", \"valid\": false, \"observed\": \"c c\", \"reason\": \"'c' is at both positions 2 and 9\"}]}\n]\n"));
+
+        let csv = render(&rows(&entries), Format::Csv);
+        assert!(csv.starts_with("line,entry,policy,valid,observed,reason\n1,1-3 a: abcde,count,true,1,\n"));
+    }
+}
@@ -0,0 +1,239 @@
+use crate::input::{self, InputError, ParseError, Source};
+use crate::output::{self, Format};
+use crate::solver::Solver;
+use regex::Regex;
+use std::fmt;
+
+mod audit;
+mod policy;
+
+use policy::{CountPolicy, PasswordPolicy, PositionPolicy};
+
+// Line of the password database. The positions of the second policy start
+// at 1, a position of 0 is left for the policy to reject.
+pub struct PasswordEntry {
+    min: usize,
+    max: usize,
+    c: char,
+    pw: String,
+}
+
+// Outcome of looking for the letter at both positions of an entry
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PositionCheck {
+    // the letter is at exactly one of the positions
+    Valid,
+    Both,
+    Neither,
+    // the position is 0 or past the end of the password
+    OutOfRange { position: usize, length: usize },
+}
+
+impl PasswordEntry {
+    // Surrounding whitespace of the line is ignored, anything else has to
+    // match the format exactly. The password can't contain whitespace.
+    fn from_line(line: &str) -> Result<PasswordEntry, ParseError> {
+        lazy_static! {
+            static ref RE: Regex = Regex::new(r"^([0-9]+)-([0-9]+) (\S): (\S+)$").unwrap();
+        }
+        let captures = RE.captures(line.trim())
+            .ok_or_else(|| ParseError::new(String::from("expected '<min>-<max> <char>: <password>'")))?;
+        let min = captures[1].parse::<usize>()?;
+        let max = captures[2].parse::<usize>()?;
+        let character = captures[3].chars().next().unwrap();
+        let pw = &captures[4];
+        return Ok(PasswordEntry {
+            min: min,
+            max: max,
+            c: character,
+            pw: pw.to_string()
+        });
+    }
+
+    // number of times the letter occurs in the password
+    pub fn count(&self) -> usize {
+        return self.pw.chars().filter(|c| *c == self.c).count();
+    }
+
+    // character at the given 1-based position
+    fn letter_at(&self, position: usize) -> Option<char> {
+        return self.pw.chars().nth(position.checked_sub(1)?);
+    }
+
+    pub fn check_positions(&self) -> PositionCheck {
+        let length = self.pw.chars().count();
+        for position in &[self.min, self.max] {
+            if *position == 0 || *position > length {
+                return PositionCheck::OutOfRange { position: *position, length: length };
+            }
+        }
+        let first_letter = self.letter_at(self.min) == Some(self.c);
+        let second_letter = self.letter_at(self.max) == Some(self.c);
+
+        match (first_letter, second_letter) {
+            (true, true) => PositionCheck::Both,
+            (false, false) => PositionCheck::Neither,
+            _ => PositionCheck::Valid,
+        }
+    }
+}
+
+impl fmt::Display for PasswordEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{} {}: {}", self.min, self.max, self.c, self.pw)
+    }
+}
+
+// Entries that don't follow the policy, with their line number and the reason
+fn violations<'a>(entries: &'a [PasswordEntry], policy: &dyn PasswordPolicy) -> Vec<(usize, &'a PasswordEntry, String)> {
+    return entries.iter()
+        .enumerate()
+        .filter_map(|(index, entry)| policy.check(entry).err().map(|reason| (index + 1, entry, reason)))
+        .collect();
+}
+
+fn load(source: &Source) -> Result<Vec<PasswordEntry>, String> {
+    let input = source.read().map_err(|e| e.to_string())?;
+    return Day2::parse(&input).map_err(|e| e.in_file(source.name()).to_string());
+}
+
+// Subcommands of `aoc 2`:
+//   policies             lists the known password policies
+//   check <policy>...    checks the database against each policy and lists
+//                        every entry that fails it, with the reason
+//   audit [policy...]    verdict of every policy on every line as a table,
+//                        or JSON or CSV with --format, by default for both
+//                        puzzle policies
+pub fn tool(name: &str, args: &[String], source: &Source, format: Format) -> Result<(), String> {
+    match (name, args) {
+        ("policies", []) => {
+            output::text_only("aoc 2 policies", format)?;
+            for (syntax, description) in policy::POLICIES {
+                println!("{:<22} {}", syntax, description);
+            }
+            return Ok(());
+        }
+        ("check", specs) if !specs.is_empty() => {
+            output::text_only("aoc 2 check", format)?;
+            let policies = specs.iter()
+                .map(|spec| policy::parse_policy(spec))
+                .collect::<Result<Vec<_>, _>>()?;
+            let entries = load(source)?;
+            for policy in policies {
+                let violations = violations(&entries, policy.as_ref());
+                println!("{}: {} of {} entries fail", policy.name(), violations.len(), entries.len());
+                for (line, entry, reason) in violations {
+                    println!("  line {}: '{}': {}", line, entry, reason);
+                }
+            }
+            return Ok(());
+        }
+        ("audit", specs) => {
+            let specs = match specs {
+                [] => vec![String::from("count"), String::from("positions")],
+                _ => specs.to_vec(),
+            };
+            let policies = specs.iter()
+                .map(|spec| policy::parse_policy(spec))
+                .collect::<Result<Vec<_>, _>>()?;
+            print!("{}", audit::render(&audit::audit(&load(source)?, &policies), format));
+            return Ok(());
+        }
+        _ => Err(String::from("usage: aoc 2 policies | aoc 2 check <policy>... | aoc 2 audit [policy...] [--format text|json|csv]")),
+    }
+}
+
+pub struct Day2;
+
+impl Solver for Day2 {
+    type Input = Vec<PasswordEntry>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Vec<PasswordEntry>, InputError> {
+        return input::lines_with(input, PasswordEntry::from_line);
+    }
+
+    fn part1(input: &Vec<PasswordEntry>) -> Option<usize> {
+        let valid_passwords = input.iter().filter(|entry| CountPolicy.check(entry).is_ok()).count();
+        return Some(valid_passwords);
+    }
+
+    fn part2(input: &Vec<PasswordEntry>) -> Option<usize> {
+        let valid_passwords = input.iter().filter(|entry| PositionPolicy.check(entry).is_ok()).count();
+        return Some(valid_passwords);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+1-3 a: abcde
+1-3 b: cdefg
+2-9 c: ccccccccc";
+
+    #[test]
+    fn example() {
+        let input = Day2::parse(EXAMPLE).unwrap();
+        assert_eq!(Day2::part1(&input), Some(2));
+        assert_eq!(Day2::part2(&input), Some(1));
+
+        let policy = policy::parse_policy("positions").unwrap();
+        let failing: Vec<(usize, String)> = violations(&input, policy.as_ref()).into_iter()
+            .map(|(line, _, reason)| (line, reason))
+            .collect();
+        assert_eq!(failing, vec![
+            (2, String::from("'b' is at neither position 1 nor 3")),
+            (3, String::from("'c' is at both positions 2 and 9")),
+        ]);
+    }
+
+    #[test]
+    fn strict_parsing() {
+        let entry = PasswordEntry::from_line("  1-3 a: abcde\r").unwrap();
+        assert_eq!((entry.min, entry.max, entry.c, entry.pw.as_str()), (1, 3, 'a', "abcde"));
+        assert_eq!(entry.to_string(), "1-3 a: abcde");
+
+        for line in &["1-3 a:abcde", "1-3 a: ab cde", "1-3 ab: abcde", "1-3 a: ", "1-3 a: abcde x"] {
+            assert!(PasswordEntry::from_line(line).is_err(), "{}", line);
+        }
+    }
+
+    #[test]
+    fn count_policy() {
+        let counts: Vec<(usize, bool)> = ["2-3 a: abc", "2-3 a: aac", "2-3 a: aaa", "2-3 a: aaaa"].iter()
+            .map(|line| PasswordEntry::from_line(line).unwrap())
+            .map(|entry| (entry.count(), CountPolicy.check(&entry).is_ok()))
+            .collect();
+        assert_eq!(counts, vec![(1, false), (2, true), (3, true), (4, false)]);
+
+        // only the positions policy needs positions starting at 1 and in order
+        let entries = Day2::parse("0-3 a: bbb\n3-1 b: bbb").unwrap();
+        assert!(CountPolicy.check(&entries[0]).is_ok());
+        assert_eq!(CountPolicy.check(&entries[1]), Err(String::from("'b' occurs 3 times, expected 3 to 1")));
+    }
+
+    #[test]
+    fn position_policy() {
+        let check = |line: &str| PasswordEntry::from_line(line).unwrap().check_positions();
+        assert_eq!(check("1-3 a: abc"), PositionCheck::Valid);
+        assert_eq!(check("1-3 c: abc"), PositionCheck::Valid);
+        assert_eq!(check("1-3 a: aba"), PositionCheck::Both);
+        assert_eq!(check("1-2 c: abc"), PositionCheck::Neither);
+        assert_eq!(check("1-5 a: abc"), PositionCheck::OutOfRange { position: 5, length: 3 });
+        assert_eq!(check("3-3 c: abc"), PositionCheck::Both);
+        assert_eq!(check("3-1 c: abc"), PositionCheck::Valid);
+        assert_eq!(check("0-3 c: abc"), PositionCheck::OutOfRange { position: 0, length: 3 });
+        assert_eq!(check("5-1 a: abc"), PositionCheck::OutOfRange { position: 5, length: 3 });
+        assert_eq!(PositionPolicy.check(&PasswordEntry::from_line("2-7 a: abc").unwrap()),
+            Err(String::from("position 7 is past the end of the 3 letter password")));
+        assert_eq!(PositionPolicy.check(&PasswordEntry::from_line("0-3 a: abc").unwrap()),
+            Err(String::from("position 0 is before the start of the password, positions start at 1")));
+
+        let entries = Day2::parse("0-3 a: bbb\n1-3 a: abc").unwrap();
+        assert_eq!(Day2::part1(&entries), Some(2));
+        assert_eq!(Day2::part2(&entries), Some(1));
+    }
+}
@@ -0,0 +1,287 @@
+use super::{PasswordEntry, PositionCheck};
+use regex::Regex;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+// A rule the entries of the password database have to follow
+pub trait PasswordPolicy {
+    // name of the policy including its parameters, as it is given on the command line
+    fn name(&self) -> String;
+    // Ok if the entry follows the policy, otherwise the reason why it doesn't
+    fn check(&self, entry: &PasswordEntry) -> Result<(), String>;
+
+    // what the policy looks at in the entry, if it is worth reporting
+    fn observed(&self, _entry: &PasswordEntry) -> Option<String> {
+        return None;
+    }
+}
+
+// syntax and description of every known policy, see `parse_policy`
+pub const POLICIES: &[(&str, &str)] = &[
+    ("count", "the letter occurs between min and max times (part 1)"),
+    ("positions", "the letter is at exactly one of the positions min and max (part 2)"),
+    ("regex:<pattern>", "the password matches the regular expression"),
+    ("entropy:<bits>", "the password has at least the given Shannon entropy"),
+    ("classes:<class>,...", "the password contains each listed class of lower, upper, digit and symbol"),
+    ("forbidden:<text>,...", "the password contains none of the listed texts"),
+];
+
+pub struct CountPolicy;
+
+impl PasswordPolicy for CountPolicy {
+    fn name(&self) -> String {
+        return String::from("count");
+    }
+
+    fn check(&self, entry: &PasswordEntry) -> Result<(), String> {
+        let occurrence = entry.count();
+        if occurrence < entry.min || occurrence > entry.max {
+            return Err(format!("'{}' occurs {} times, expected {} to {}", entry.c, occurrence, entry.min, entry.max));
+        }
+        return Ok(());
+    }
+
+    fn observed(&self, entry: &PasswordEntry) -> Option<String> {
+        return Some(entry.count().to_string());
+    }
+}
+
+pub struct PositionPolicy;
+
+impl PasswordPolicy for PositionPolicy {
+    fn name(&self) -> String {
+        return String::from("positions");
+    }
+
+    fn check(&self, entry: &PasswordEntry) -> Result<(), String> {
+        match entry.check_positions() {
+            PositionCheck::Valid => Ok(()),
+            PositionCheck::Both => Err(format!("'{}' is at both positions {} and {}", entry.c, entry.min, entry.max)),
+            PositionCheck::Neither => Err(format!("'{}' is at neither position {} nor {}", entry.c, entry.min, entry.max)),
+            PositionCheck::OutOfRange { position: 0, .. } => {
+                Err(String::from("position 0 is before the start of the password, positions start at 1"))
+            }
+            PositionCheck::OutOfRange { position, length } => {
+                Err(format!("position {} is past the end of the {} letter password", position, length))
+            }
+        }
+    }
+
+    // the letters at both positions, '-' past the end of the password
+    fn observed(&self, entry: &PasswordEntry) -> Option<String> {
+        let letters: Vec<String> = [entry.min, entry.max].iter()
+            .map(|position| entry.letter_at(*position).unwrap_or('-').to_string())
+            .collect();
+        return Some(letters.join(" "));
+    }
+}
+
+pub struct RegexPolicy {
+    regex: Regex,
+}
+
+impl PasswordPolicy for RegexPolicy {
+    fn name(&self) -> String {
+        return format!("regex:{}", self.regex);
+    }
+
+    fn check(&self, entry: &PasswordEntry) -> Result<(), String> {
+        if !self.regex.is_match(&entry.pw) {
+            return Err(format!("does not match /{}/", self.regex));
+        }
+        return Ok(());
+    }
+}
+
+// Shannon entropy of a password in bits, summed over all of its characters
+pub fn entropy(password: &str) -> f64 {
+    let mut counts: HashMap<char, usize> = HashMap::new();
+    for c in password.chars() {
+        *counts.entry(c).or_insert(0) += 1;
+    }
+    let length = password.chars().count() as f64;
+    return counts.values()
+        .map(|count| {
+            let count = *count as f64;
+            count * (length / count).log2()
+        })
+        .sum();
+}
+
+pub struct EntropyPolicy {
+    bits: f64,
+}
+
+impl PasswordPolicy for EntropyPolicy {
+    fn name(&self) -> String {
+        return format!("entropy:{}", self.bits);
+    }
+
+    fn check(&self, entry: &PasswordEntry) -> Result<(), String> {
+        let bits = entropy(&entry.pw);
+        if bits < self.bits {
+            return Err(format!("entropy of {:.2} bits is below {}", bits, self.bits));
+        }
+        return Ok(());
+    }
+
+    fn observed(&self, entry: &PasswordEntry) -> Option<String> {
+        return Some(format!("{:.2}", entropy(&entry.pw)));
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CharClass {
+    Lower,
+    Upper,
+    Digit,
+    // anything else
+    Symbol,
+}
+
+impl CharClass {
+    fn of(c: char) -> CharClass {
+        if c.is_lowercase() {
+            return CharClass::Lower;
+        } else if c.is_uppercase() {
+            return CharClass::Upper;
+        } else if c.is_ascii_digit() {
+            return CharClass::Digit;
+        }
+        return CharClass::Symbol;
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            CharClass::Lower => "lower",
+            CharClass::Upper => "upper",
+            CharClass::Digit => "digit",
+            CharClass::Symbol => "symbol",
+        }
+    }
+}
+
+impl FromStr for CharClass {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<CharClass, String> {
+        match s {
+            "lower" => Ok(CharClass::Lower),
+            "upper" => Ok(CharClass::Upper),
+            "digit" => Ok(CharClass::Digit),
+            "symbol" => Ok(CharClass::Symbol),
+            _ => Err(format!("unknown character class '{}', expected lower, upper, digit or symbol", s)),
+        }
+    }
+}
+
+pub struct ClassPolicy {
+    classes: Vec<CharClass>,
+}
+
+impl PasswordPolicy for ClassPolicy {
+    fn name(&self) -> String {
+        let classes: Vec<&str> = self.classes.iter().map(|class| class.name()).collect();
+        return format!("classes:{}", classes.join(","));
+    }
+
+    fn check(&self, entry: &PasswordEntry) -> Result<(), String> {
+        let missing: Vec<&str> = self.classes.iter()
+            .filter(|class| !entry.pw.chars().any(|c| CharClass::of(c) == **class))
+            .map(|class| class.name())
+            .collect();
+        if !missing.is_empty() {
+            return Err(format!("no {} character", missing.join(" or ")));
+        }
+        return Ok(());
+    }
+}
+
+pub struct ForbiddenPolicy {
+    texts: Vec<String>,
+}
+
+impl PasswordPolicy for ForbiddenPolicy {
+    fn name(&self) -> String {
+        return format!("forbidden:{}", self.texts.join(","));
+    }
+
+    fn check(&self, entry: &PasswordEntry) -> Result<(), String> {
+        match self.texts.iter().find(|text| entry.pw.contains(text.as_str())) {
+            Some(text) => Err(format!("contains '{}'", text)),
+            None => Ok(()),
+        }
+    }
+}
+
+// Creates a policy from its command line syntax, see `POLICIES`
+pub fn parse_policy(spec: &str) -> Result<Box<dyn PasswordPolicy>, String> {
+    let (kind, argument) = match spec.split_once(':') {
+        Some((kind, argument)) => (kind, Some(argument)),
+        None => (spec, None),
+    };
+
+    match (kind, argument) {
+        ("count", None) => Ok(Box::new(CountPolicy)),
+        ("positions", None) => Ok(Box::new(PositionPolicy)),
+        ("regex", Some(pattern)) => {
+            let regex = Regex::new(pattern).map_err(|e| format!("invalid regex '{}': {}", pattern, e))?;
+            Ok(Box::new(RegexPolicy { regex: regex }))
+        }
+        ("entropy", Some(bits)) => {
+            let bits = bits.parse::<f64>().map_err(|_| format!("invalid number of bits '{}'", bits))?;
+            Ok(Box::new(EntropyPolicy { bits: bits }))
+        }
+        ("classes", Some(classes)) => {
+            let classes = classes.split(',').map(|class| class.parse::<CharClass>()).collect::<Result<_, _>>()?;
+            Ok(Box::new(ClassPolicy { classes: classes }))
+        }
+        ("forbidden", Some(texts)) => {
+            let texts = texts.split(',').filter(|text| !text.is_empty()).map(String::from).collect();
+            Ok(Box::new(ForbiddenPolicy { texts: texts }))
+        }
+        _ => {
+            let known: Vec<&str> = POLICIES.iter().map(|(syntax, _)| *syntax).collect();
+            Err(format!("unknown password policy '{}', expected one of {}", spec, known.join(", ")))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reasons(spec: &str, lines: &[&str]) -> Vec<Result<(), String>> {
+        let policy = parse_policy(spec).unwrap();
+        return lines.iter()
+            .map(|line| policy.check(&PasswordEntry::from_line(line).unwrap()))
+            .collect();
+    }
+
+    #[test]
+    fn registry() {
+        for spec in &["count", "positions", "regex:^[a-z]+$", "entropy:2.5", "classes:lower,digit", "forbidden:abc,123"] {
+            assert_eq!(parse_policy(spec).unwrap().name(), *spec);
+        }
+        assert!(parse_policy("length").is_err());
+        assert!(parse_policy("count:3").is_err());
+        assert!(parse_policy("classes:lower,emoji").is_err());
+        assert!(parse_policy("regex:(").is_err());
+    }
+
+    #[test]
+    fn policies() {
+        assert_eq!(reasons("count", &["1-3 a: abcde", "1-3 b: cdefg"]),
+            vec![Ok(()), Err(String::from("'b' occurs 0 times, expected 1 to 3"))]);
+        assert_eq!(reasons("regex:^[a-e]+$", &["1-3 a: abcde", "1-3 b: cdefg"]),
+            vec![Ok(()), Err(String::from("does not match /^[a-e]+$/"))]);
+        assert_eq!(reasons("entropy:4", &["1-3 a: abcd", "1-3 a: aaab"]),
+            vec![Ok(()), Err(String::from("entropy of 3.25 bits is below 4"))]);
+        assert_eq!(reasons("classes:upper,digit,symbol", &["1-3 a: aB3!", "1-3 a: abc"]),
+            vec![Ok(()), Err(String::from("no upper or digit or symbol character"))]);
+        assert_eq!(reasons("forbidden:cd,xyz", &["1-3 a: abcde", "1-3 a: xy"]),
+            vec![Err(String::from("contains 'cd'")), Ok(())]);
+        assert!((entropy("aabb") - 4.0).abs() < 1e-9);
+        assert_eq!(format!("{:.2}", entropy("aaaa")), "0.00");
+    }
+}
@@ -0,0 +1,190 @@
+use crate::grid::{Edge, Grid};
+use crate::input::{InputError, ParseError, Source};
+use crate::output::{self, Format};
+use crate::solver::Solver;
+use std::fs;
+use std::convert::TryFrom;
+use std::fmt;
+
+mod render;
+mod toboggan;
+
+use toboggan::Slope;
+
+const DEFAULT_SEARCH_RANGE: i64 = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Square {
+    Open,
+    Tree,
+}
+
+impl TryFrom<char> for Square {
+    type Error = ParseError;
+
+    fn try_from(c: char) -> Result<Square, ParseError> {
+        match c {
+            '.' => Ok(Square::Open),
+            '#' => Ok(Square::Tree),
+            _ => Err(ParseError::new(format!("unknown square '{}'", c))),
+        }
+    }
+}
+
+impl fmt::Display for Square {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Square::Open => write!(f, "."),
+            Square::Tree => write!(f, "#"),
+        }
+    }
+}
+
+fn load(source: &Source) -> Result<Grid<Square>, String> {
+    let input = source.read().map_err(|e| e.to_string())?;
+    return Day3::parse(&input).map_err(|e| e.in_file(source.name()).to_string());
+}
+
+fn print_counts(map: &Grid<Square>, slopes: &[Slope]) {
+    let counts = toboggan::count_trees(map, slopes);
+    for (slope, trees) in slopes.iter().zip(counts.iter()) {
+        println!("{}: {} trees", slope, trees);
+    }
+    match toboggan::product(&counts) {
+        Some(product) => println!("product: {}", product),
+        None => println!("product: overflows u128"),
+    }
+}
+
+// Subcommands of `aoc 3`:
+//   slopes <right,down>...     trees hit on each slope and their product,
+//                              steps can be fractions like 1/2
+//   slopes --config <file>     the same for the slopes listed in a file, one
+//                              per line
+//   best [range]               whole number slope with the fewest trees, for
+//                              steps of up to `range` squares (default 10)
+//   render [options] [right,down...]
+//                              the map with the route of every slope (by
+//                              default the puzzle slopes) next to each other,
+//                              --colour draws them on a single map instead,
+//                              --text <file> and --svg <file> write the
+//                              plain text or an SVG image to a file, maps
+//                              wider than 10000 columns are refused
+pub fn tool(name: &str, args: &[String], source: &Source, format: Format) -> Result<(), String> {
+    output::text_only(&format!("aoc 3 {}", name), format)?;
+    match (name, args) {
+        ("slopes", [flag, file]) if flag == "--config" => {
+            let config = Source::from_arg(file);
+            let text = config.read().map_err(|e| e.to_string())?;
+            let slopes = toboggan::parse_slopes(&text).map_err(|e| e.in_file(config.name()).to_string())?;
+            print_counts(&load(source)?, &slopes);
+            return Ok(());
+        }
+        ("slopes", slopes) if !slopes.is_empty() => {
+            let slopes = slopes.iter()
+                .map(|slope| slope.parse::<Slope>().map_err(|e| e.to_string()))
+                .collect::<Result<Vec<_>, _>>()?;
+            print_counts(&load(source)?, &slopes);
+            return Ok(());
+        }
+        ("best", []) | ("best", [_]) => {
+            let range = match args.first() {
+                Some(range) => range.parse::<i64>().ok().filter(|range| *range > 0)
+                    .ok_or(format!("invalid search range '{}'", range))?,
+                None => DEFAULT_SEARCH_RANGE,
+            };
+            match toboggan::best_slope(&load(source)?, range) {
+                Some((slope, trees)) => println!("{}: {} trees", slope, trees),
+                None => println!("no slope in range"),
+            }
+            return Ok(());
+        }
+        ("render", args) => {
+            let mut colour = false;
+            let mut export = None;
+            let mut slopes = Vec::new();
+            let mut args = args.iter();
+            while let Some(arg) = args.next() {
+                match arg.as_str() {
+                    "--colour" => colour = true,
+                    "--text" | "--svg" => {
+                        let file = args.next().ok_or(format!("missing file for {}", arg))?;
+                        export = Some((arg.as_str(), file));
+                    }
+                    slope => slopes.push(slope.parse::<Slope>().map_err(|e| e.to_string())?),
+                }
+            }
+            if colour && export.is_some() {
+                return Err(String::from("--colour only works in the terminal, not with --text or --svg"));
+            }
+            if slopes.is_empty() {
+                slopes = toboggan::PUZZLE_SLOPES.iter().map(|(right, down)| Slope::integer(*right, *down)).collect();
+            }
+
+            let map = load(source)?;
+            match export {
+                Some(("--svg", file)) => {
+                    fs::write(file, render::svg(&map, &slopes)?).map_err(|e| format!("{}: {}", file, e))?;
+                }
+                Some((_, file)) => {
+                    fs::write(file, render::side_by_side(&map, &slopes)?).map_err(|e| format!("{}: {}", file, e))?;
+                }
+                None if colour => print!("{}", render::coloured(&map, &slopes)?),
+                None => print!("{}", render::side_by_side(&map, &slopes)?),
+            }
+            return Ok(());
+        }
+        _ => Err(String::from("usage: aoc 3 slopes <right,down>... | aoc 3 slopes --config <file> | aoc 3 best [range] \
+| aoc 3 render [--colour|--text <file>|--svg <file>] [right,down...]")),
+    }
+}
+
+pub struct Day3;
+
+impl Solver for Day3 {
+    type Input = Grid<Square>;
+    type Answer1 = u64;
+    type Answer2 = u128;
+
+    fn parse(input: &str) -> Result<Grid<Square>, InputError> {
+        return Grid::parse(input, Edge::WrapX);
+    }
+
+    fn part1(map: &Grid<Square>) -> Option<u64> {
+        return Some(toboggan::trees(map, &Slope::integer(3, 1)));
+    }
+
+    fn part2(map: &Grid<Square>) -> Option<u128> {
+        let slopes: Vec<Slope> = toboggan::PUZZLE_SLOPES.iter()
+            .map(|(right, down)| Slope::integer(*right, *down))
+            .collect();
+        return toboggan::product(&toboggan::count_trees(map, &slopes));
+    }
+}
+
+// example map of the puzzle, used by the tests of all submodules
+#[cfg(test)]
+const EXAMPLE: &str = "\
+..##.......
+#...#...#..
+.#....#..#.
+..#.#...#.#
+.#...##..#.
+..#.##.....
+.#.#.#....#
+.#........#
+#.##...#...
+#...##....#
+.#..#...#.#";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn example() {
+        let input = Day3::parse(EXAMPLE).unwrap();
+        assert_eq!(Day3::part1(&input), Some(7));
+        assert_eq!(Day3::part2(&input), Some(336));
+    }
+}
@@ -0,0 +1,199 @@
+use super::Square;
+use super::toboggan::{self, Slope};
+use crate::grid::Grid;
+use std::collections::HashMap;
+
+const RESET: &str = "\x1b[0m";
+// terminal and SVG colours of the slopes, reused if there are more slopes
+const ANSI_COLOURS: &[&str] = &["\x1b[1;31m", "\x1b[1;34m", "\x1b[1;33m", "\x1b[1;35m", "\x1b[1;36m", "\x1b[1;32m"];
+const SVG_COLOURS: &[&str] = &["#d62728", "#1f77b4", "#ff7f0e", "#9467bd", "#17becf", "#2ca02c"];
+// size of a square in the SVG, in pixels
+const CELL: usize = 12;
+// widest map that is rendered, enough for the puzzle slopes on the puzzle input
+const MAX_COLUMNS: i128 = 10_000;
+
+// Copies of the map that are shown, as the range of columns [first, last].
+// Always covers the original map and whole copies of it. An error if that
+// is wider than MAX_COLUMNS, before anything is drawn.
+fn columns(map: &Grid<Square>, paths: &[Vec<(usize, isize)>]) -> Result<(isize, isize), String> {
+    let width = map.width().max(1) as i128;
+    let cols = paths.iter().flatten().map(|(_, col)| *col as i128);
+    let first = cols.clone().min().unwrap_or(0).min(0).div_euclid(width) * width;
+    let last = (cols.max().unwrap_or(0).max(width - 1).div_euclid(width) + 1) * width - 1;
+    if last - first + 1 > MAX_COLUMNS {
+        return Err(format!("the routes are {} columns wide, at most {} can be rendered", last - first + 1, MAX_COLUMNS));
+    }
+    return Ok((first as isize, last as isize));
+}
+
+// 'O' for visited open squares, 'X' for trees that were hit
+fn marks(map: &Grid<Square>, path: &[(usize, isize)]) -> HashMap<(usize, isize), char> {
+    return path.iter()
+        .map(|(row, col)| match map.get(*row as isize, *col) {
+            Some(Square::Tree) => ((*row, *col), 'X'),
+            _ => ((*row, *col), 'O'),
+        })
+        .collect();
+}
+
+fn square(map: &Grid<Square>, row: usize, col: isize) -> char {
+    match map.get(row as isize, col) {
+        Some(Square::Tree) => '#',
+        _ => '.',
+    }
+}
+
+// paths of all slopes, an error if one of them can't be drawn
+fn paths(map: &Grid<Square>, slopes: &[Slope]) -> Result<Vec<Vec<(usize, isize)>>, String> {
+    return slopes.iter().map(|slope| toboggan::path(map, slope).map_err(|e| e.to_string())).collect();
+}
+
+// The route of every slope on its own copy of the map, next to each other
+pub fn side_by_side(map: &Grid<Square>, slopes: &[Slope]) -> Result<String, String> {
+    let mut panels: Vec<Vec<String>> = Vec::new();
+    for (slope, path) in slopes.iter().zip(paths(map, slopes)?) {
+        let marks = marks(map, &path);
+        let (first, last) = columns(map, &[path])?;
+        let mut lines = vec![slope.to_string()];
+        for row in 0..map.height() {
+            lines.push((first..=last)
+                .map(|col| marks.get(&(row, col)).copied().unwrap_or_else(|| square(map, row, col)))
+                .collect());
+        }
+        panels.push(lines);
+    }
+
+    let widths: Vec<usize> = panels.iter()
+        .map(|lines| lines.iter().map(|line| line.chars().count()).max().unwrap_or(0))
+        .collect();
+
+    let mut out = String::new();
+    for line in 0..=map.height() {
+        let parts: Vec<String> = panels.iter().zip(widths.iter())
+            .map(|(lines, width)| format!("{:<width$}", lines[line], width = width))
+            .collect();
+        out.push_str(parts.join("   ").trim_end());
+        out.push('\n');
+    }
+    return Ok(out);
+}
+
+// All slopes on a single map, each in its own terminal colour. Squares
+// visited by several slopes get the colour of the first one.
+pub fn coloured(map: &Grid<Square>, slopes: &[Slope]) -> Result<String, String> {
+    let paths = paths(map, slopes)?;
+    let (first, last) = columns(map, &paths)?;
+    let mut marked: HashMap<(usize, isize), (char, &str)> = HashMap::new();
+    for (path, colour) in paths.iter().zip(ANSI_COLOURS.iter().cycle()) {
+        for (position, mark) in marks(map, path) {
+            marked.entry(position).or_insert((mark, colour));
+        }
+    }
+
+    let mut out = String::new();
+    for (slope, colour) in slopes.iter().zip(ANSI_COLOURS.iter().cycle()) {
+        out.push_str(&format!("{}{}{}\n", colour, slope, RESET));
+    }
+    for row in 0..map.height() {
+        for col in first..=last {
+            match marked.get(&(row, col)) {
+                Some((mark, colour)) => out.push_str(&format!("{}{}{}", colour, mark, RESET)),
+                None => out.push(square(map, row, col)),
+            }
+        }
+        out.push('\n');
+    }
+    return Ok(out);
+}
+
+// All slopes on a single map as an SVG image, each slope is drawn as a line
+// in its own colour with its marks on the visited squares
+pub fn svg(map: &Grid<Square>, slopes: &[Slope]) -> Result<String, String> {
+    let paths = paths(map, slopes)?;
+    let (first, last) = columns(map, &paths)?;
+    let width = (last - first + 1) as usize * CELL;
+    let height = map.height() * CELL;
+    let x = |col: isize| (col - first) as usize * CELL;
+
+    let mut lines = vec![
+        format!("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\">", width, height, width, height),
+        format!("  <rect width=\"{}\" height=\"{}\" fill=\"#ffffff\"/>", width, height),
+    ];
+    for row in 0..map.height() {
+        for col in first..=last {
+            if square(map, row, col) == '#' {
+                lines.push(format!("  <rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"#a5d6a7\"/>", x(col), row * CELL, CELL, CELL));
+            }
+        }
+    }
+
+    for ((slope, path), colour) in slopes.iter().zip(paths.iter()).zip(SVG_COLOURS.iter().cycle()) {
+        let centre = CELL / 2;
+        let points: Vec<String> = path.iter()
+            .map(|(row, col)| format!("{},{}", x(*col) + centre, row * CELL + centre))
+            .collect();
+        lines.push(format!("  <g stroke=\"{}\" fill=\"{}\">", colour, colour));
+        lines.push(format!("    <title>{}: {} trees</title>", slope, toboggan::trees(map, slope)));
+        lines.push(format!("    <polyline points=\"{}\" fill=\"none\" stroke-opacity=\"0.5\"/>", points.join(" ")));
+        let mut marks: Vec<((usize, isize), char)> = marks(map, path).into_iter().collect();
+        marks.sort_unstable();
+        for ((row, col), mark) in marks {
+            lines.push(format!("    <text x=\"{}\" y=\"{}\" font-family=\"monospace\" font-size=\"{}\" text-anchor=\"middle\" dominant-baseline=\"central\" stroke=\"none\">{}</text>",
+                x(col) + centre, row * CELL + centre, CELL, mark));
+        }
+        lines.push(String::from("  </g>"));
+    }
+    lines.push(String::from("</svg>"));
+    return Ok(lines.join("\n") + "\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day3::{Day3, EXAMPLE};
+    use crate::solver::Solver;
+
+    #[test]
+    fn text() {
+        let map = Day3::parse(EXAMPLE).unwrap();
+        let rendered = side_by_side(&map, &[Slope::integer(3, 1)]).unwrap();
+        let lines: Vec<&str> = rendered.lines().collect();
+        assert_eq!(lines.len(), 12);
+        assert_eq!(lines[0], "right 3, down 1");
+        assert_eq!(lines[2], "#..O#...#..#...#...#..#...#...#..");
+        assert_eq!(lines[3], ".#....X..#..#....#..#..#....#..#.");
+        assert_eq!(lines[11], ".#..#...#.#.#..#...#.#.#..#...X.#");
+
+        // going left adds copies of the map on the left
+        let rendered = side_by_side(&map, &[Slope::integer(1, 2), Slope::integer(-1, 1)]).unwrap();
+        let lines: Vec<&str> = rendered.lines().collect();
+        assert_eq!(lines[0], "right 1, down 2   right -1, down 1");
+        assert_eq!(lines[3], ".X....#..#.       .#....#..X..#....#..#.");
+    }
+
+    #[test]
+    fn colour_and_svg() {
+        let map = Day3::parse(EXAMPLE).unwrap();
+        let slopes = [Slope::integer(1, 1), Slope::integer(3, 1)];
+        let rendered = coloured(&map, &slopes).unwrap();
+        assert!(rendered.starts_with("\x1b[1;31mright 1, down 1\x1b[0m\n\x1b[1;34mright 3, down 1\x1b[0m\n"));
+        assert!(rendered.lines().nth(3).unwrap().starts_with("#\x1b[1;31mO\x1b[0m.\x1b[1;34mO\x1b[0m#"));
+
+        let svg = svg(&map, &slopes).unwrap();
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"396\" height=\"132\""));
+        assert!(svg.contains("    <title>right 3, down 1: 7 trees</title>"));
+        assert_eq!(svg.matches(">X</text>").count(), 2 + 7);
+        assert!(svg.ends_with("</svg>\n"));
+    }
+
+    #[test]
+    fn width_limit() {
+        let map = Day3::parse(EXAMPLE).unwrap();
+        // 10 steps of 999 columns, rounded up to whole copies of the map
+        assert_eq!(side_by_side(&map, &[Slope::integer(999, 1)]).unwrap().lines().nth(1).unwrap().len(), 9999);
+        for slope in &[Slope::integer(1000, 1), Slope::integer(-1000, 1), Slope::integer(i64::MAX / 11, 1)] {
+            let error = svg(&map, &[*slope]).unwrap_err();
+            assert!(error.starts_with("the routes are ") && error.ends_with(" columns wide, at most 10000 can be rendered"), "{}", error);
+        }
+    }
+}
@@ -0,0 +1,265 @@
+use super::Square;
+use crate::grid::Grid;
+use crate::input::{InputError, ParseError};
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
+
+// can't overflow as long as neither value is i64::MIN
+fn gcd(a: i64, b: i64) -> i64 {
+    if b == 0 {
+        return a.abs();
+    }
+    return gcd(b, a % b);
+}
+
+// Fraction in lowest terms, the denominator is always positive. Neither
+// part is i64::MIN, so both can be negated.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rational {
+    numerator: i64,
+    denominator: i64,
+}
+
+impl Rational {
+    pub fn new(numerator: i64, denominator: i64) -> Result<Rational, ParseError> {
+        if denominator == 0 {
+            return Err(ParseError::new(String::from("denominator can't be 0")));
+        }
+        if numerator == i64::MIN || denominator == i64::MIN {
+            return Err(ParseError::new(format!("{} is out of range", i64::MIN)));
+        }
+        let divisor = gcd(numerator, denominator) * denominator.signum();
+        return Ok(Rational {
+            numerator: numerator / divisor,
+            denominator: denominator / divisor,
+        });
+    }
+
+    pub fn integer(value: i64) -> Rational {
+        return Rational { numerator: value, denominator: 1 };
+    }
+}
+
+// `<numerator>/<denominator>` or an integer
+impl FromStr for Rational {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Rational, ParseError> {
+        match s.split_once('/') {
+            Some((numerator, denominator)) => Rational::new(numerator.trim().parse()?, denominator.trim().parse()?),
+            None => Rational::new(s.trim().parse()?, 1),
+        }
+    }
+}
+
+impl fmt::Display for Rational {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.denominator {
+            1 => write!(f, "{}", self.numerator),
+            _ => write!(f, "{}/{}", self.numerator, self.denominator),
+        }
+    }
+}
+
+// Direction the toboggan travels in. Negative steps to the right go left, a
+// negative step down starts at the bottom of the map and goes up.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Slope {
+    right: Rational,
+    down: Rational,
+    // see `step`
+    step: (i64, i64),
+}
+
+// the slopes of the second part of the puzzle
+pub const PUZZLE_SLOPES: &[(i64, i64)] = &[(1, 1), (3, 1), (5, 1), (7, 1), (1, 2)];
+
+impl Slope {
+    pub fn new(right: Rational, down: Rational) -> Result<Slope, ParseError> {
+        if down.numerator == 0 {
+            return Err(ParseError::new(String::from("the slope has to go down or up")));
+        }
+        let step = whole_step(right, down)
+            .ok_or_else(|| ParseError::new(format!("the whole steps of right {}, down {} are too large", right, down)))?;
+        return Ok(Slope { right: right, down: down, step: step });
+    }
+
+    pub fn integer(right: i64, down: i64) -> Slope {
+        assert_ne!(down, 0, "the slope has to go down or up");
+        return Slope {
+            right: Rational::integer(right),
+            down: Rational::integer(down),
+            step: (right, down),
+        };
+    }
+
+    // Smallest whole step (right, down) along the slope. The toboggan only
+    // visits squares, so fractional positions in between are skipped.
+    pub fn step(&self) -> (i64, i64) {
+        return self.step;
+    }
+}
+
+// None if the step doesn't fit into an i64
+fn whole_step(right: Rational, down: Rational) -> Option<(i64, i64)> {
+    let (a, b) = (right.denominator, down.denominator);
+    let multiple = (a / gcd(a, b)).checked_mul(b)?;
+    return Some((right.numerator.checked_mul(multiple / a)?, down.numerator.checked_mul(multiple / b)?));
+}
+
+// `<right>,<down>`, both may be fractions
+impl FromStr for Slope {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Slope, ParseError> {
+        let (right, down) = s.split_once(',')
+            .ok_or_else(|| ParseError::new(format!("expected '<right>,<down>', found '{}'", s)))?;
+        return Slope::new(right.parse()?, down.parse()?);
+    }
+}
+
+impl fmt::Display for Slope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "right {}, down {}", self.right, self.down)
+    }
+}
+
+// Slope config file: one slope per line, blank lines and `#` comments are ignored
+pub fn parse_slopes(input: &str) -> Result<Vec<Slope>, InputError> {
+    let mut slopes = Vec::new();
+    for (idx, line) in input.lines().enumerate() {
+        let slope = line.split('#').next().unwrap_or("").trim();
+        if slope.is_empty() {
+            continue;
+        }
+        slopes.push(slope.parse::<Slope>().map_err(|e| InputError::parse(idx + 1, line, e))?);
+    }
+    return Ok(slopes);
+}
+
+// Rows visited from the start until the toboggan leaves the map, with the
+// number of steps taken to reach them
+fn rows(map: &Grid<Square>, slope: &Slope) -> impl Iterator<Item = (usize, i64)> {
+    let (_, down) = slope.step();
+    let height = map.height() as i64;
+    let start = if down < 0 { height - 1 } else { 0 };
+    return (0..).map_while(move |steps: i64| {
+        let row = steps.checked_mul(down).and_then(|offset| start.checked_add(offset))?;
+        match (0..height).contains(&row) {
+            true => Some((row as usize, steps)),
+            false => None,
+        }
+    });
+}
+
+// Squares (row, column) visited from the start until the toboggan leaves the
+// map, including the start. Columns are not wrapped, they count the squares
+// travelled to the right of the original map. An error if a column doesn't
+// fit into an isize.
+pub fn path(map: &Grid<Square>, slope: &Slope) -> Result<Vec<(usize, isize)>, ParseError> {
+    let (right, _) = slope.step();
+    return rows(map, slope)
+        .map(|(row, steps)| {
+            let col = steps.checked_mul(right).and_then(|col| isize::try_from(col).ok())
+                .ok_or_else(|| ParseError::new(format!("{} leaves the map too far to the side", slope)))?;
+            Ok((row, col))
+        })
+        .collect();
+}
+
+pub fn trees(map: &Grid<Square>, slope: &Slope) -> u64 {
+    let (right, _) = slope.step();
+    // the map repeats to the right, so only the column within it matters
+    let width = map.width().max(1) as i128;
+    return rows(map, slope)
+        .map(|(row, steps)| (row, (steps as i128 * right as i128).rem_euclid(width)))
+        .filter(|(row, col)| map.get(*row as isize, *col as isize) == Some(&Square::Tree))
+        .count() as u64;
+}
+
+// trees hit on each slope, in the given order
+pub fn count_trees(map: &Grid<Square>, slopes: &[Slope]) -> Vec<u64> {
+    return slopes.iter().map(|slope| trees(map, slope)).collect();
+}
+
+// None if the product overflows
+pub fn product(counts: &[u64]) -> Option<u128> {
+    return counts.iter().try_fold(1u128, |product, count| product.checked_mul(*count as u128));
+}
+
+// The whole number slope with the fewest trees, for steps of up to `range`
+// squares left or right and 1 to `range` squares down. Ties go to the slope
+// found first, with the smallest step down and then the leftmost step.
+pub fn best_slope(map: &Grid<Square>, range: i64) -> Option<(Slope, u64)> {
+    let mut best: Option<(Slope, u64)> = None;
+    for down in 1..=range {
+        for right in -range..=range {
+            let slope = Slope::integer(right, down);
+            let trees = trees(map, &slope);
+            match best {
+                Some((_, fewest)) if trees >= fewest => (),
+                _ => best = Some((slope, trees)),
+            }
+        }
+    }
+    return best;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day3::{Day3, EXAMPLE};
+    use crate::solver::Solver;
+
+    #[test]
+    fn slopes() {
+        assert_eq!("-3/6".parse::<Rational>().unwrap(), Rational::new(1, -2).unwrap());
+        assert_eq!("1/-2".parse::<Rational>().unwrap().to_string(), "-1/2");
+        assert!("1/0".parse::<Rational>().is_err());
+        assert!("1,0".parse::<Slope>().is_err());
+        assert!("3".parse::<Slope>().is_err());
+
+        let step = |slope: &str| slope.parse::<Slope>().unwrap().step();
+        assert_eq!(step("3,1"), (3, 1));
+        assert_eq!(step("1/2,1"), (1, 2));
+        assert_eq!(step("2/3,1/2"), (4, 3));
+        assert_eq!(step("-1,-1/3"), (-3, -1));
+
+        // values that would overflow are rejected
+        assert!("1/-9223372036854775808,1".parse::<Slope>().is_err());
+        assert!("-9223372036854775808,1".parse::<Slope>().is_err());
+        assert_eq!("1/3037000499,1/3037000507".parse::<Slope>().unwrap_err().to_string(),
+            "the whole steps of right 1/3037000499, down 1/3037000507 are too large");
+        assert!("-9223372036854775807/2,1/3".parse::<Slope>().is_err());
+
+        let slopes = parse_slopes("# puzzle slopes\n3,1\n\n1/2, 1  # fractions work too\n").unwrap();
+        let steps: Vec<(i64, i64)> = slopes.iter().map(|slope| slope.step()).collect();
+        assert_eq!(steps, vec![(3, 1), (1, 2)]);
+        assert_eq!(parse_slopes("1,1\nabc").unwrap_err().to_string(), "<input>:2: expected '<right>,<down>', found 'abc' (in 'abc')");
+    }
+
+    #[test]
+    fn tree_counts() {
+        let map = Day3::parse(EXAMPLE).unwrap();
+        let slopes: Vec<Slope> = PUZZLE_SLOPES.iter().map(|(right, down)| Slope::integer(*right, *down)).collect();
+        let counts = count_trees(&map, &slopes);
+        assert_eq!(counts, vec![2, 7, 3, 4, 2]);
+        assert_eq!(product(&counts), Some(336));
+        assert_eq!(product(&[u64::MAX, u64::MAX, 2]), None);
+
+        // going up from the bottom left, or to the left, just mirrors the path
+        assert_eq!(path(&map, &Slope::integer(-3, -1)).unwrap().first(), Some(&(10, 0)));
+        assert_eq!(path(&map, &Slope::integer(-3, -1)).unwrap().last(), Some(&(0, -30)));
+        assert_eq!(trees(&map, &"1/2,1".parse().unwrap()), 2);
+
+        // huge steps to the side wrap around the map, but can't be drawn
+        let slope = Slope::integer(i64::MAX, 1);
+        assert_eq!(trees(&map, &slope), trees(&map, &Slope::integer(i64::MAX % 11, 1)));
+        assert_eq!(path(&map, &slope).unwrap_err().to_string(), "right 9223372036854775807, down 1 leaves the map too far to the side");
+        assert_eq!(path(&map, &Slope::integer(1, i64::MAX)).unwrap(), vec![(0, 0)]);
+
+        let (slope, trees) = best_slope(&map, 3).unwrap();
+        assert_eq!((slope.to_string(), trees), (String::from("right 1, down 3"), 0));
+    }
+}
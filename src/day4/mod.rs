@@ -0,0 +1,250 @@
+use std::collections::HashMap;
+use crate::input::{InputError, Source};
+use crate::output::{self, Format};
+use crate::solver::{self, Solver};
+
+mod records;
+mod schema;
+
+use records::{Options, Severity, Warning};
+use schema::Schema;
+
+#[derive(Debug)]
+pub struct Passport {
+    fields: HashMap<String, String>,
+    // first and last line of the record in the batch file
+    lines: (usize, usize),
+}
+
+// passports of a batch file with the warnings about its records
+pub struct Batch {
+    passports: Vec<Passport>,
+    warnings: Vec<Warning>,
+}
+
+lazy_static! {
+    static ref SCHEMA: Schema = Schema::parse(schema::DEFAULT_SCHEMA).unwrap();
+}
+
+// The puzzle answers count a record with a duplicate key as a passport that
+// may be invalid, they don't reject the whole batch file.
+const SOLVER_OPTIONS: Options = Options {
+    duplicate_keys: Severity::Warn,
+    unknown_keys: Severity::Warn,
+};
+
+// Arguments of the validating subcommands: an optional schema file and the
+// severity of duplicate and unknown keys
+struct Arguments {
+    // None for the schema of the puzzle
+    schema: Option<Schema>,
+    options: Options,
+}
+
+impl Arguments {
+    fn schema(&self) -> &Schema {
+        return self.schema.as_ref().unwrap_or(&SCHEMA);
+    }
+}
+
+fn parse_arguments(args: &[String]) -> Result<Arguments, String> {
+    let mut schema_file = None;
+    let mut options = records::DEFAULT_OPTIONS;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--duplicate-keys" | "--unknown-keys" => {
+                let value = args.next().ok_or(format!("missing value for {}", arg))?;
+                let severity = value.parse::<Severity>()?;
+                match arg.as_str() {
+                    "--duplicate-keys" => options.duplicate_keys = severity,
+                    _ => options.unknown_keys = severity,
+                }
+            }
+            flag if flag.starts_with("--") => return Err(format!("unknown option {}\n{}", flag, USAGE)),
+            file if schema_file.is_none() => schema_file = Some(Source::from_arg(file)),
+            _ => return Err(String::from(USAGE)),
+        }
+    }
+
+    let schema = match schema_file {
+        Some(file) => {
+            let text = file.read().map_err(|e| e.to_string())?;
+            Some(Schema::parse(&text).map_err(|e| e.in_file(file.name()).to_string())?)
+        }
+        None => None,
+    };
+    return Ok(Arguments { schema: schema, options: options });
+}
+
+// passports of the batch file, warnings are printed to stderr
+fn load(source: &Source, arguments: &Arguments) -> Result<Vec<Passport>, String> {
+    let input = source.read().map_err(|e| e.to_string())?;
+    let (passports, warnings) = records::parse(&input, arguments.schema(), arguments.options)
+        .map_err(|e| e.in_file(source.name()).to_string())?;
+    for warning in warnings {
+        eprintln!("warning: {}:{}: {}", source.name(), warning.line, warning.message);
+    }
+    return Ok(passports);
+}
+
+const USAGE: &str = "usage: aoc 4 schema | aoc 4 validate [options] [schema file] | aoc 4 report [options] [schema file]
+options: --duplicate-keys warn|error, --unknown-keys warn|error";
+
+// Subcommands of `aoc 4`:
+//   schema                 prints the default passport schema
+//   validate [schema]      counts the passports that have all required fields
+//                          and the ones that are valid according to the
+//                          schema file, by default the one of the puzzle
+//   report [schema]        lists every violation of the schema by passport,
+//                          with the lines of its record
+// Duplicate keys in a record are errors and keys the schema doesn't know are
+// warnings, `--duplicate-keys` and `--unknown-keys` change that.
+pub fn tool(name: &str, args: &[String], source: &Source, format: Format) -> Result<(), String> {
+    output::text_only(&format!("aoc 4 {}", name), format)?;
+    match name {
+        "schema" if args.is_empty() => {
+            print!("{}", schema::DEFAULT_SCHEMA);
+            return Ok(());
+        }
+        "validate" => {
+            let arguments = parse_arguments(args)?;
+            let passports = load(source, &arguments)?;
+            let schema = arguments.schema();
+            let complete = passports.iter().filter(|p| schema.has_required_fields(p)).count();
+            let valid = passports.iter().filter(|p| schema.is_valid(p)).count();
+            println!("{} passports, {} with all required fields, {} valid", passports.len(), complete, valid);
+            return Ok(());
+        }
+        "report" => {
+            let arguments = parse_arguments(args)?;
+            let passports = load(source, &arguments)?;
+            let mut valid = 0;
+            for (index, passport) in passports.iter().enumerate() {
+                let errors = arguments.schema().validate(passport);
+                if errors.is_empty() {
+                    valid += 1;
+                }
+                let (first, last) = passport.lines;
+                for error in errors {
+                    println!("passport {} (lines {}-{}): {}", index + 1, first, last, error);
+                }
+            }
+            println!("{} of {} passports are valid", valid, passports.len());
+            return Ok(());
+        }
+        _ => Err(String::from(USAGE)),
+    }
+}
+
+pub struct Day4;
+
+impl Solver for Day4 {
+    type Input = Batch;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Batch, InputError> {
+        let (passports, warnings) = records::parse(input, &SCHEMA, SOLVER_OPTIONS)?;
+        return Ok(Batch { passports: passports, warnings: warnings });
+    }
+
+    fn warnings(batch: &Batch) -> Vec<solver::Warning> {
+        return batch.warnings.iter().map(|warning| (warning.line, warning.message.clone())).collect();
+    }
+
+    fn part1(batch: &Batch) -> Option<usize> {
+        let valid_passports = batch.passports.iter().filter(|p| SCHEMA.has_required_fields(p)).count();
+        return Some(valid_passports);
+    }
+
+    fn part2(batch: &Batch) -> Option<usize> {
+        let valid_passports = batch.passports.iter().filter(|p| SCHEMA.is_valid(p)).count();
+        return Some(valid_passports);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+ecl:gry pid:860033327 eyr:2020 hcl:#fffffd
+byr:1937 iyr:2017 cid:147 hgt:183cm
+
+iyr:2013 ecl:amb cid:350 eyr:2023 pid:028048884
+hcl:#cfa07d byr:1929
+
+hcl:#ae17e1 iyr:2013
+eyr:2024
+ecl:brn pid:760753108 byr:1931
+hgt:179cm
+
+hcl:#cfa07d eyr:2025 pid:166559648
+iyr:2011 ecl:brn hgt:59in";
+
+    const INVALID: &str = "\
+eyr:1972 cid:100
+hcl:#18171d ecl:amb hgt:170 pid:186cm iyr:2018 byr:1926
+
+iyr:2019
+hcl:#602927 eyr:1967 hgt:170cm
+ecl:grn pid:012533040 byr:1946
+
+hcl:dab227 iyr:2012
+ecl:brn hgt:182cm pid:021572410 eyr:2020 byr:1992 cid:277
+
+hgt:59cm ecl:zzz
+eyr:2038 hcl:74454a iyr:2023
+pid:3556412378 byr:2007";
+
+    const VALID: &str = "\
+pid:087499704 hgt:74in ecl:grn iyr:2012 eyr:2030 byr:1980
+hcl:#623a2f
+
+eyr:2029 ecl:blu cid:129 byr:1989
+iyr:2014 pid:896056539 hcl:#a97842 hgt:165cm
+
+hcl:#888785
+hgt:164cm byr:2001 iyr:2015 cid:88
+pid:545766238 ecl:hzl
+eyr:2022
+
+iyr:2010 hgt:158cm hcl:#b6652a ecl:blu byr:1944 eyr:2021 pid:093154719";
+
+    #[test]
+    fn example() {
+        let input = Day4::parse(EXAMPLE).unwrap();
+        assert_eq!(Day4::part1(&input), Some(2));
+        assert_eq!(Day4::part2(&input), Some(2));
+    }
+
+    #[test]
+    fn strict_validation() {
+        let invalid = Day4::parse(INVALID).unwrap();
+        assert_eq!(Day4::part1(&invalid), Some(4));
+        assert_eq!(Day4::part2(&invalid), Some(0));
+
+        let valid = Day4::parse(VALID).unwrap();
+        assert_eq!(Day4::part2(&valid), Some(4));
+    }
+
+    #[test]
+    fn duplicate_keys() {
+        // the first value is kept, the batch file is still accepted
+        let input = Day4::parse("ecl:gry ecl:xyz pid:860033327 eyr:2020 hcl:#fffffd\nbyr:1937 iyr:2017 hgt:183cm").unwrap();
+        assert_eq!(Day4::part1(&input), Some(1));
+        assert_eq!(Day4::part2(&input), Some(1));
+        assert_eq!(Day4::warnings(&input), vec![(1, String::from("duplicate key 'ecl'"))]);
+    }
+
+    #[test]
+    fn arguments() {
+        let args = |args: &[&str]| parse_arguments(&args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>());
+        let arguments = args(&["--unknown-keys", "error"]).unwrap();
+        assert!(arguments.schema.is_none());
+        assert_eq!(arguments.options, Options { duplicate_keys: Severity::Error, unknown_keys: Severity::Error });
+        assert!(args(&["--strict"]).err().unwrap().starts_with("unknown option --strict\n"));
+        assert!(args(&["--unknown-keys"]).is_err());
+    }
+}
@@ -0,0 +1,160 @@
+// Batch files of passports: records of `<key>:<value>` pairs, separated by
+// any whitespace, with blank lines between the records.
+
+use super::Passport;
+use super::schema::Schema;
+use crate::input::{self, InputError, ParseError};
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+// How a suspicious record is treated
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Severity {
+    // the record is kept and a warning is reported
+    Warn,
+    // parsing fails
+    Error,
+}
+
+impl FromStr for Severity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Severity, String> {
+        match s {
+            "warn" => Ok(Severity::Warn),
+            "error" => Ok(Severity::Error),
+            _ => Err(format!("unknown severity '{}', expected warn or error", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Options {
+    // a key that appears twice in a record, the first value is kept
+    pub duplicate_keys: Severity,
+    // a key the schema doesn't declare
+    pub unknown_keys: Severity,
+}
+
+pub const DEFAULT_OPTIONS: Options = Options {
+    duplicate_keys: Severity::Error,
+    unknown_keys: Severity::Warn,
+};
+
+// Problem found in a record that doesn't stop the parser
+#[derive(Debug, Clone, PartialEq)]
+pub struct Warning {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+// the key value pairs of a single line
+struct RecordLine {
+    text: String,
+    pairs: Vec<(String, String)>,
+}
+
+impl FromStr for RecordLine {
+    type Err = ParseError;
+
+    fn from_str(line: &str) -> Result<RecordLine, ParseError> {
+        let pairs = line.split_whitespace()
+            .map(|pair| match pair.split_once(':') {
+                Some((key, value)) if !key.is_empty() => Ok((key.to_string(), value.to_string())),
+                _ => Err(ParseError::new(format!("expected '<key>:<value>', found '{}'", pair))),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        return Ok(RecordLine { text: line.to_string(), pairs: pairs });
+    }
+}
+
+// Reports the problem as a warning, or fails the parser, depending on the severity
+fn report(severity: Severity, line: usize, text: &str, message: String, warnings: &mut Vec<Warning>) -> Result<(), InputError> {
+    match severity {
+        Severity::Warn => {
+            warnings.push(Warning { line: line, message: message });
+            return Ok(());
+        }
+        Severity::Error => return Err(InputError::parse(line, text, ParseError::new(message))),
+    }
+}
+
+// All passports of a batch file with the warnings about them. Keys are
+// unknown if the schema doesn't declare them.
+pub fn parse(input: &str, schema: &Schema, options: Options) -> Result<(Vec<Passport>, Vec<Warning>), InputError> {
+    let groups = input::collect_groups_as::<RecordLine>(input)?;
+
+    let mut passports = Vec::new();
+    let mut warnings = Vec::new();
+    for (lines, span) in groups {
+        let mut fields: HashMap<String, String> = HashMap::new();
+        for (offset, line) in lines.iter().enumerate() {
+            // groups hold no blank lines, so the lines of a record are consecutive
+            let number = span.0 + offset;
+            for (key, value) in &line.pairs {
+                if fields.contains_key(key) {
+                    report(options.duplicate_keys, number, &line.text, format!("duplicate key '{}'", key), &mut warnings)?;
+                    continue;
+                }
+                if !schema.fields.iter().any(|field| field.name == *key) {
+                    report(options.unknown_keys, number, &line.text, format!("unknown key '{}'", key), &mut warnings)?;
+                }
+                fields.insert(key.clone(), value.clone());
+            }
+        }
+        passports.push(Passport { fields: fields, lines: span });
+    }
+    return Ok((passports, warnings));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day4::schema::DEFAULT_SCHEMA;
+
+    fn schema() -> Schema {
+        return Schema::parse(DEFAULT_SCHEMA).unwrap();
+    }
+
+    #[test]
+    fn whitespace_and_spans() {
+        let input = "\r\necl:gry\tpid:860033327   eyr:2020\r\n  byr:1937\r\n \r\n\r\n\r\nhcl:#cfa07d\r\n\r\n\r\n";
+        let (passports, warnings) = parse(input, &schema(), DEFAULT_OPTIONS).unwrap();
+        assert!(warnings.is_empty());
+        let spans: Vec<(usize, usize)> = passports.iter().map(|passport| passport.lines).collect();
+        assert_eq!(spans, vec![(2, 3), (7, 7)]);
+        assert_eq!(passports[0].fields.len(), 4);
+        assert_eq!(passports[0].fields["byr"], "1937");
+        assert_eq!(passports[1].fields["hcl"], "#cfa07d");
+    }
+
+    #[test]
+    fn malformed_tokens() {
+        let error = parse("byr:1937\n\nbyr:1937 iyr\n", &schema(), DEFAULT_OPTIONS).unwrap_err();
+        assert_eq!(error.to_string(), "<input>:3: expected '<key>:<value>', found 'iyr' (in 'byr:1937 iyr')");
+        assert!(parse(":1937", &schema(), DEFAULT_OPTIONS).is_err());
+    }
+
+    #[test]
+    fn duplicate_and_unknown_keys() {
+        let input = "byr:1937 foo:1\nbyr:1938";
+        let error = parse(input, &schema(), DEFAULT_OPTIONS).unwrap_err();
+        assert_eq!(error.to_string(), "<input>:2: duplicate key 'byr' (in 'byr:1938')");
+
+        let lenient = Options { duplicate_keys: Severity::Warn, unknown_keys: Severity::Warn };
+        let (passports, warnings) = parse(input, &schema(), lenient).unwrap();
+        assert_eq!(passports[0].fields["byr"], "1937");
+        let warnings: Vec<String> = warnings.iter().map(|warning| warning.to_string()).collect();
+        assert_eq!(warnings, vec!["line 1: unknown key 'foo'", "line 2: duplicate key 'byr'"]);
+
+        let strict = Options { duplicate_keys: Severity::Warn, unknown_keys: Severity::Error };
+        assert_eq!(parse(input, &schema(), strict).unwrap_err().to_string(), "<input>:1: unknown key 'foo' (in 'byr:1937 foo:1')");
+    }
+}
@@ -0,0 +1,519 @@
+// Passport schemas, written in a small subset of TOML:
+//
+//   [<field>]                  starts the declaration of a field
+//   <key> = <value>            strings ("..." or '...'), integers, booleans
+//                              and single line arrays of those
+//   # comment
+//
+// See passport.toml for the keys and rules of a field.
+
+use super::Passport;
+use crate::input::{InputError, ParseError};
+use regex::Regex;
+use std::error::Error;
+use std::fmt;
+use std::iter::Peekable;
+use std::str::Chars;
+
+// the schema of the puzzle
+pub const DEFAULT_SCHEMA: &str = include_str!("passport.toml");
+
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    Int(i64),
+    Str(String),
+    Bool(bool),
+    Array(Vec<Value>),
+}
+
+impl Value {
+    fn kind(&self) -> &'static str {
+        match self {
+            Value::Int(_) => "an integer",
+            Value::Str(_) => "a string",
+            Value::Bool(_) => "a boolean",
+            Value::Array(_) => "an array",
+        }
+    }
+}
+
+struct ValueParser<'a> {
+    chars: Peekable<Chars<'a>>,
+}
+
+impl<'a> ValueParser<'a> {
+    fn skip_whitespace(&mut self) {
+        while self.chars.peek().is_some_and(|c| c.is_whitespace()) {
+            self.chars.next();
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, ParseError> {
+        self.skip_whitespace();
+        match self.chars.peek() {
+            Some('"') | Some('\'') => {
+                let quote = self.chars.next().unwrap();
+                return Ok(Value::Str(self.string(quote)?));
+            }
+            Some('[') => {
+                self.chars.next();
+                return self.array();
+            }
+            _ => return self.bare(),
+        }
+    }
+
+    // Strings in double quotes know the escapes \" and \\, strings in single
+    // quotes are taken literally
+    fn string(&mut self, quote: char) -> Result<String, ParseError> {
+        let mut string = String::new();
+        loop {
+            match self.chars.next() {
+                None => return Err(ParseError::new(String::from("unterminated string"))),
+                Some(c) if c == quote => return Ok(string),
+                Some('\\') if quote == '"' => match self.chars.next() {
+                    Some(c) if c == '"' || c == '\\' => string.push(c),
+                    Some(c) => return Err(ParseError::new(format!("unknown escape '\\{}'", c))),
+                    None => return Err(ParseError::new(String::from("unterminated string"))),
+                },
+                Some(c) => string.push(c),
+            }
+        }
+    }
+
+    fn array(&mut self) -> Result<Value, ParseError> {
+        let mut values = Vec::new();
+        loop {
+            self.skip_whitespace();
+            if self.chars.peek() == Some(&']') {
+                self.chars.next();
+                return Ok(Value::Array(values));
+            }
+            values.push(self.value()?);
+            self.skip_whitespace();
+            match self.chars.next() {
+                Some(',') => (),
+                Some(']') => return Ok(Value::Array(values)),
+                _ => return Err(ParseError::new(String::from("expected ',' or ']' in array"))),
+            }
+        }
+    }
+
+    // integers and booleans
+    fn bare(&mut self) -> Result<Value, ParseError> {
+        let mut word = String::new();
+        while let Some(c) = self.chars.peek() {
+            if !(c.is_ascii_alphanumeric() || *c == '-' || *c == '+' || *c == '_') {
+                break;
+            }
+            word.push(*c);
+            self.chars.next();
+        }
+        match word.as_str() {
+            "true" => Ok(Value::Bool(true)),
+            "false" => Ok(Value::Bool(false)),
+            "" => Err(ParseError::new(String::from("expected a value"))),
+            _ => {
+                let number = word.replace('_', "").parse::<i64>()
+                    .map_err(|_| ParseError::new(format!("invalid value '{}'", word)))?;
+                Ok(Value::Int(number))
+            }
+        }
+    }
+}
+
+// a whole value, only a comment may follow it
+fn parse_value(text: &str) -> Result<Value, ParseError> {
+    let mut parser = ValueParser { chars: text.chars().peekable() };
+    let value = parser.value()?;
+    parser.skip_whitespace();
+    match parser.chars.next() {
+        None | Some('#') => return Ok(value),
+        Some(c) => return Err(ParseError::new(format!("unexpected '{}' after value", c))),
+    }
+}
+
+fn is_key(key: &str) -> bool {
+    return !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.');
+}
+
+// key and value with the line they were declared on
+struct Entry {
+    key: String,
+    value: Value,
+    line: usize,
+    text: String,
+}
+
+impl Entry {
+    fn error(&self, message: String) -> InputError {
+        return InputError::parse(self.line, &self.text, ParseError::new(message));
+    }
+
+    fn expected(&self, kind: &str) -> InputError {
+        return self.error(format!("expected {} for '{}', found {}", kind, self.key, self.value.kind()));
+    }
+
+    fn int(&self) -> Result<i64, InputError> {
+        match self.value {
+            Value::Int(value) => Ok(value),
+            _ => Err(self.expected("an integer")),
+        }
+    }
+
+    fn string(&self) -> Result<&str, InputError> {
+        match &self.value {
+            Value::Str(value) => Ok(value),
+            _ => Err(self.expected("a string")),
+        }
+    }
+
+    fn bool(&self) -> Result<bool, InputError> {
+        match self.value {
+            Value::Bool(value) => Ok(value),
+            _ => Err(self.expected("a boolean")),
+        }
+    }
+
+    fn strings(&self) -> Result<Vec<String>, InputError> {
+        match &self.value {
+            Value::Array(values) => values.iter()
+                .map(|value| match value {
+                    Value::Str(value) => Ok(value.clone()),
+                    _ => Err(self.expected("an array of strings")),
+                })
+                .collect(),
+            _ => Err(self.expected("an array of strings")),
+        }
+    }
+
+    // [min, max]
+    fn range(&self) -> Result<(i64, i64), InputError> {
+        match self.value {
+            Value::Array(ref values) => match values.as_slice() {
+                [Value::Int(min), Value::Int(max)] => Ok((*min, *max)),
+                _ => Err(self.expected("[min, max]")),
+            },
+            _ => Err(self.expected("[min, max]")),
+        }
+    }
+}
+
+struct Section {
+    name: String,
+    line: usize,
+    text: String,
+    entries: Vec<Entry>,
+}
+
+impl Section {
+    fn get(&self, key: &str) -> Option<&Entry> {
+        return self.entries.iter().find(|entry| entry.key == key);
+    }
+
+    // errors about the section as a whole point to its header
+    fn error(&self, message: String) -> InputError {
+        return InputError::parse(self.line, &self.text, ParseError::new(message));
+    }
+
+    fn require(&self, key: &str, rule: &str) -> Result<&Entry, InputError> {
+        return self.get(key).ok_or_else(|| self.error(format!("rule '{}' of '{}' needs '{}'", rule, self.name, key)));
+    }
+}
+
+fn parse_sections(text: &str) -> Result<Vec<Section>, InputError> {
+    let mut sections: Vec<Section> = Vec::new();
+    for (idx, line) in text.lines().enumerate() {
+        let error = |message: String| InputError::parse(idx + 1, line, ParseError::new(message));
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+
+        if let Some(rest) = trimmed.strip_prefix('[') {
+            let (name, after) = rest.split_once(']').ok_or_else(|| error(String::from("expected ']'")))?;
+            let after = after.trim();
+            if !after.is_empty() && !after.starts_with('#') {
+                return Err(error(format!("unexpected '{}' after section", after)));
+            }
+            let name = name.trim();
+            if !is_key(name) {
+                return Err(error(format!("invalid field name '{}'", name)));
+            }
+            if sections.iter().any(|section| section.name == name) {
+                return Err(error(format!("field '{}' is declared twice", name)));
+            }
+            sections.push(Section { name: name.to_string(), line: idx + 1, text: line.to_string(), entries: Vec::new() });
+            continue;
+        }
+
+        let (key, value) = trimmed.split_once('=').ok_or_else(|| error(String::from("expected '<key> = <value>' or '[<field>]'")))?;
+        let key = key.trim();
+        if !is_key(key) {
+            return Err(error(format!("invalid key '{}'", key)));
+        }
+        let value = parse_value(value).map_err(|e| InputError::parse(idx + 1, line, e))?;
+        let section = sections.last_mut().ok_or_else(|| error(String::from("key outside of a field")))?;
+        if section.get(key).is_some() {
+            return Err(error(format!("key '{}' is set twice", key)));
+        }
+        section.entries.push(Entry { key: key.to_string(), value: value, line: idx + 1, text: line.to_string() });
+    }
+    return Ok(sections);
+}
+
+#[derive(Debug)]
+pub enum Rule {
+    // the field only has to be present
+    Present,
+    IntRange { min: i64, max: i64 },
+    Regex(Regex),
+    Enum(Vec<String>),
+    // a number followed by one of the units, with the range of each unit
+    UnitRange(Vec<(String, i64, i64)>),
+}
+
+// Whole numbers are plain digits, so signs, spaces and numbers too large for
+// an i64 are rejected instead of overflowing
+fn parse_number(value: &str) -> Option<i64> {
+    if value.is_empty() || !value.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    return value.parse::<i64>().ok();
+}
+
+impl Rule {
+    fn allows(&self, value: &str) -> bool {
+        match self {
+            Rule::Present => true,
+            Rule::IntRange { min, max } => parse_number(value).is_some_and(|v| (*min..=*max).contains(&v)),
+            Rule::Regex(regex) => regex.is_match(value),
+            Rule::Enum(values) => values.iter().any(|v| v == value),
+            Rule::UnitRange(units) => units.iter().any(|(unit, min, max)| {
+                value.strip_suffix(unit.as_str())
+                    .and_then(parse_number)
+                    .is_some_and(|v| (*min..=*max).contains(&v))
+            }),
+        }
+    }
+
+    // what is wrong with a value the rule doesn't allow, e.g. "does not match /re/"
+    fn violation(&self) -> String {
+        match self {
+            Rule::Present => String::from("is not present"),
+            Rule::IntRange { min, max } => format!("is not a whole number from {} to {}", min, max),
+            Rule::Regex(regex) => format!("does not match /{}/", regex),
+            Rule::Enum(values) => format!("is not one of {}", values.join(", ")),
+            Rule::UnitRange(units) => {
+                let ranges: Vec<String> = units.iter().map(|(unit, min, max)| format!("{} to {} {}", min, max, unit)).collect();
+                format!("is not a measure of {}", ranges.join(" or "))
+            }
+        }
+    }
+}
+
+// Field of a passport that violates the schema
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldError {
+    pub field: String,
+    // None if a required field is missing
+    pub value: Option<String>,
+    // how the value violates the rule, "required" for a missing field
+    pub constraint: String,
+}
+
+impl fmt::Display for FieldError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.value {
+            None => write!(f, "{} is missing", self.field),
+            Some(value) => write!(f, "{}: '{}' {}", self.field, value, self.constraint),
+        }
+    }
+}
+
+impl Error for FieldError {}
+
+#[derive(Debug)]
+pub struct Field {
+    pub name: String,
+    pub required: bool,
+    pub rule: Rule,
+}
+
+impl Field {
+    fn from_section(section: &Section) -> Result<Field, InputError> {
+        let required = match section.get("required") {
+            Some(entry) => entry.bool()?,
+            None => false,
+        };
+        let rule_name = match section.get("rule") {
+            Some(entry) => Some(entry.string()?),
+            None => None,
+        };
+
+        let (rule, keys): (Rule, &[&str]) = match rule_name {
+            None => (Rule::Present, &[]),
+            Some("int") => {
+                let rule = Rule::IntRange {
+                    min: section.require("min", "int")?.int()?,
+                    max: section.require("max", "int")?.int()?,
+                };
+                (rule, &["min", "max"])
+            }
+            Some("regex") => {
+                let entry = section.require("pattern", "regex")?;
+                let regex = Regex::new(entry.string()?).map_err(|e| entry.error(format!("invalid pattern: {}", e)))?;
+                (Rule::Regex(regex), &["pattern"])
+            }
+            Some("enum") => (Rule::Enum(section.require("values", "enum")?.strings()?), &["values"]),
+            Some("unit_range") => {
+                let mut units = Vec::new();
+                for entry in section.entries.iter() {
+                    if let Some(unit) = entry.key.strip_prefix("unit.") {
+                        let (min, max) = entry.range()?;
+                        units.push((unit.to_string(), min, max));
+                    }
+                }
+                if units.is_empty() {
+                    return Err(section.error(format!("rule 'unit_range' of '{}' needs 'unit.<name>'", section.name)));
+                }
+                (Rule::UnitRange(units), &[])
+            }
+            Some(other) => {
+                let entry = section.get("rule").unwrap();
+                return Err(entry.error(format!("unknown rule '{}', expected int, regex, enum or unit_range", other)));
+            }
+        };
+
+        for entry in section.entries.iter() {
+            let known = entry.key == "required" || entry.key == "rule" || keys.contains(&entry.key.as_str())
+                || (matches!(rule, Rule::UnitRange(_)) && entry.key.starts_with("unit."));
+            if !known {
+                return Err(entry.error(format!("unknown key '{}' for field '{}'", entry.key, section.name)));
+            }
+        }
+
+        return Ok(Field {
+            name: section.name.clone(),
+            required: required,
+            rule: rule,
+        });
+    }
+
+    // value of the field in a passport, None if the passport doesn't have it
+    pub fn validate(&self, value: Option<&str>) -> Result<(), FieldError> {
+        let error = |constraint: String| FieldError {
+            field: self.name.clone(),
+            value: value.map(String::from),
+            constraint: constraint,
+        };
+        match value {
+            None if self.required => Err(error(String::from("required"))),
+            None => Ok(()),
+            Some(value) if !self.rule.allows(value) => Err(error(self.rule.violation())),
+            Some(_) => Ok(()),
+        }
+    }
+}
+
+// Fields a passport may have, and the rules their values follow
+#[derive(Debug)]
+pub struct Schema {
+    pub fields: Vec<Field>,
+}
+
+impl Schema {
+    pub fn parse(text: &str) -> Result<Schema, InputError> {
+        let fields = parse_sections(text)?.iter()
+            .map(Field::from_section)
+            .collect::<Result<Vec<_>, _>>()?;
+        return Ok(Schema { fields: fields });
+    }
+
+    pub fn has_required_fields(&self, passport: &Passport) -> bool {
+        return self.fields.iter()
+            .filter(|field| field.required)
+            .all(|field| passport.fields.contains_key(&field.name));
+    }
+
+    // Every missing required field and every declared field whose value
+    // breaks its rule, in the order of the schema
+    pub fn validate(&self, passport: &Passport) -> Vec<FieldError> {
+        return self.fields.iter()
+            .filter_map(|field| field.validate(passport.fields.get(&field.name).map(String::as_str)).err())
+            .collect();
+    }
+
+    pub fn is_valid(&self, passport: &Passport) -> bool {
+        return self.validate(passport).is_empty();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn values() {
+        assert_eq!(parse_value(" 1_920 # year").unwrap(), Value::Int(1920));
+        assert_eq!(parse_value("'^#[0-9]$'").unwrap(), Value::Str(String::from("^#[0-9]$")));
+        assert_eq!(parse_value(r#""a \"b\" \\ c""#).unwrap(), Value::Str(String::from(r#"a "b" \ c"#)));
+        assert_eq!(parse_value("[150, -3, [true]]").unwrap(),
+            Value::Array(vec![Value::Int(150), Value::Int(-3), Value::Array(vec![Value::Bool(true)])]));
+        assert!(parse_value("\"open").is_err());
+        assert!(parse_value("[1 2]").is_err());
+        assert!(parse_value("12 34").is_err());
+    }
+
+    #[test]
+    fn default_schema() {
+        let schema = Schema::parse(DEFAULT_SCHEMA).unwrap();
+        let names: Vec<&str> = schema.fields.iter().map(|field| field.name.as_str()).collect();
+        assert_eq!(names, vec!["byr", "iyr", "eyr", "hgt", "hcl", "ecl", "pid", "cid"]);
+        assert_eq!(schema.fields.iter().filter(|field| field.required).count(), 7);
+
+        let allows = |name: &str, value: &str| {
+            return schema.fields.iter().find(|field| field.name == name).unwrap().validate(Some(value)).is_ok();
+        };
+        assert!(allows("byr", "2002") && !allows("byr", "2003") && !allows("byr", "abc") && !allows("byr", "+1990"));
+        assert!(allows("hgt", "60in") && allows("hgt", "190cm"));
+        assert!(!allows("hgt", "190in") && !allows("hgt", "190") && !allows("hgt", "99999999999999999999cm") && !allows("hgt", "-60in"));
+        assert!(allows("hcl", "#123abc") && !allows("hcl", "#123abz"));
+        assert!(allows("ecl", "brn") && !allows("ecl", "wat"));
+        assert!(allows("pid", "000000001") && !allows("pid", "0123456789"));
+        assert!(allows("cid", "anything"));
+    }
+
+    #[test]
+    fn field_errors() {
+        let schema = Schema::parse(DEFAULT_SCHEMA).unwrap();
+        let passport = Passport {
+            fields: [("byr", "abc"), ("hgt", "999999999999999999999cm"), ("ecl", "brn"), ("pid", "12")].iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect(),
+            lines: (1, 1),
+        };
+        let errors: Vec<String> = schema.validate(&passport).iter().map(|error| error.to_string()).collect();
+        assert_eq!(errors, vec![
+            "byr: 'abc' is not a whole number from 1920 to 2002",
+            "iyr is missing",
+            "eyr is missing",
+            "hgt: '999999999999999999999cm' is not a measure of 150 to 193 cm or 59 to 76 in",
+            "hcl is missing",
+            "pid: '12' does not match /^[0-9]{9}$/",
+        ]);
+        assert_eq!(schema.validate(&passport)[1], FieldError { field: String::from("iyr"), value: None, constraint: String::from("required") });
+    }
+
+    #[test]
+    fn schema_errors() {
+        let error = |text: &str| Schema::parse(text).unwrap_err().to_string();
+        assert_eq!(error("[byr]\nrule = \"int\"\nmin = 1"), "<input>:1: rule 'int' of 'byr' needs 'max' (in '[byr]')");
+        assert_eq!(error("[byr]\nrule = \"float\""), "<input>:2: unknown rule 'float', expected int, regex, enum or unit_range (in 'rule = \"float\"')");
+        assert_eq!(error("[byr]\nrequired = 1"), "<input>:2: expected a boolean for 'required', found an integer (in 'required = 1')");
+        assert_eq!(error("[byr]\nrequired = true\nmax = 3"), "<input>:3: unknown key 'max' for field 'byr' (in 'max = 3')");
+        assert_eq!(error("min = 3"), "<input>:1: key outside of a field (in 'min = 3')");
+        assert_eq!(error("[byr]\n[byr]"), "<input>:2: field 'byr' is declared twice (in '[byr]')");
+        assert_eq!(error("[hgt]\nrule = \"unit_range\"\nunit.cm = [1]"), "<input>:3: expected [min, max] for 'unit.cm', found an array (in 'unit.cm = [1]')");
+    }
+}
@@ -1,60 +1,87 @@
-use crate::input;
+use crate::input::{self, InputError, ParseError};
+use crate::solver::Solver;
 
-fn find_entry(iter: &mut std::str::Chars, lower: u32, upper: u32, down: char, up: char) -> u32 {
+fn find_entry(iter: &mut std::str::Chars, lower: u32, upper: u32, down: char, up: char) -> Result<u32, ParseError> {
     let mut min = lower;
     let mut max = upper;
 
     while min != max {
-        let p = iter.next().unwrap();
+        let p = iter.next().ok_or_else(|| ParseError::new(String::from("partition is too short")))?;
         if p == down {
             max = (max - min) / 2 + min;
         } else if p == up {
             min = (max - min) / 2 + 1 + min;
 
         } else {
-            panic!("invalid char")
+            return Err(ParseError::new(format!("invalid char '{}', expected '{}' or '{}'", p, down, up)));
         }
     }
-    return min;
+    return Ok(min);
 
 }
 
-fn get_row(iter: &mut std::str::Chars) -> u32 {
+fn get_row(iter: &mut std::str::Chars) -> Result<u32, ParseError> {
     return find_entry(iter, 0, 127, 'F', 'B');
 }
 
-fn get_column(iter: &mut std::str::Chars) -> u32 {
+fn get_column(iter: &mut std::str::Chars) -> Result<u32, ParseError> {
     return find_entry(iter, 0, 7, 'L', 'R');
 }
 
-fn get_id(partition: &String) -> u32 {
+fn get_id(partition: &str) -> Result<u32, ParseError> {
     let mut iter = partition.chars();
-    let row = get_row(&mut iter);
-    let column = get_column(&mut iter);
-    return row * 8 + column;
+    let row = get_row(&mut iter)?;
+    let column = get_column(&mut iter)?;
+    return Ok(row * 8 + column);
 }
 
-pub fn question1() -> String {
-    let filename = "input/day5.txt";
-    let input = input::lines_as::<String>(filename);
-    let max = input.iter().map(get_id).max().unwrap();
-    return format!("Day 5.1: maxium id = {}", max);
-}
+pub struct Day5;
+
+impl Solver for Day5 {
+    type Input = Vec<u32>;
+    type Answer1 = u32;
+    type Answer2 = u32;
+
+    fn parse(input: &str) -> Result<Vec<u32>, InputError> {
+        return input::lines_with(input, get_id);
+    }
+
+    fn part1(ids: &Vec<u32>) -> Option<u32> {
+        return ids.iter().max().copied();
+    }
 
-pub fn question2() -> String {
-    let filename = "input/day5.txt";
-    let input = input::lines_as::<String>(filename);
-    let mut ids = input.iter().map(get_id).collect::<Vec<u32>>();
-    ids.sort();
+    fn part2(ids: &Vec<u32>) -> Option<u32> {
+        let mut ids = ids.clone();
+        ids.sort();
 
-    for i in 1..ids.len()-1 {
-        let prev = ids[i-1];
-        let current = ids[i];
+        for i in 1..ids.len() {
+            let prev = ids[i-1];
+            let current = ids[i];
 
-        if current == prev + 2 {
-            return format!("Day 5.2: my id = {}", prev + 1);
+            if current == prev + 2 {
+                return Some(prev + 1);
+            }
         }
+        return None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seat_ids() {
+        assert_eq!(get_id("FBFBBFFRLR").unwrap(), 357);
+        assert_eq!(get_id("BFFFBBFRRR").unwrap(), 567);
+        assert_eq!(get_id("FFFBBBFRRR").unwrap(), 119);
+        assert_eq!(get_id("BBFFBBFRLL").unwrap(), 820);
+        assert!(get_id("BBFFBBFRL").is_err());
     }
 
-    return format!("{}", "not found");
-}
\ No newline at end of file
+    #[test]
+    fn example() {
+        let input = Day5::parse("FBFBBFFRLR\nBFFFBBFRRR\nFFFBBBFRRR\nBBFFBBFRLL").unwrap();
+        assert_eq!(Day5::part1(&input), Some(820));
+    }
+}
@@ -1,45 +1,82 @@
-use crate::input;
+use crate::input::{self, InputError};
+use crate::solver::Solver;
 use std::collections::{HashSet, HashMap};
 
-pub fn question1() -> String {
-    let filename = "input/day6.txt";
-    let input = input::collect_groups_as::<String>(filename);
+pub struct Day6;
 
-    let mut sum = 0;
-    for group in input {
-        let answers: HashSet<char> = group.join("").chars().collect();
-        sum += answers.len();
+impl Solver for Day6 {
+    type Input = Vec<Vec<String>>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Vec<Vec<String>>, InputError> {
+        let groups = input::collect_groups_as::<String>(input)?;
+        return Ok(groups.into_iter().map(|(group, _)| group).collect());
     }
 
-    return format!("Day 6.1: sum of answers = {}", sum);
-}
+    fn part1(groups: &Vec<Vec<String>>) -> Option<usize> {
+        let mut sum = 0;
+        for group in groups {
+            let answers: HashSet<char> = group.join("").chars().collect();
+            sum += answers.len();
+        }
+        return Some(sum);
+    }
 
+    fn part2(groups: &Vec<Vec<String>>) -> Option<usize> {
+        let mut sum = 0;
+        for group in groups {
+            let mut answers: HashMap<char, usize> = HashMap::new();
 
-pub fn question2() -> String {
-    let filename = "input/day6.txt";
-    let input = input::collect_groups_as::<String>(filename);
+            for person in group {
+                for answer in person.chars() {
+                    *answers.entry(answer).or_insert(0) += 1;
+                }
+            }
 
-    let mut sum = 0;
-    for group in input {
-        let mut answers: HashMap<char, usize> = HashMap::new();
+            let number_of_people_in_group = group.len();
+            let answered_by_all = answers.values().filter(|v| **v == number_of_people_in_group).count();
 
-        for person in &group {
-            for answer in person.chars() {
-                *answers.entry(answer).or_insert(0) += 1;
-            }
+            sum += answered_by_all;
         }
+        return Some(sum);
+    }
+}
 
-        let number_of_people_in_group = group.len();
-        let answered_by_all: u32 = answers.iter().map(|(_, v)| {
-            if *v == number_of_people_in_group {
-                return 1;
-            } else {
-                return 0;
-            }
-        }).sum();
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+abc
+
+a
+b
+c
+
+ab
+ac
 
-        sum += answered_by_all;
+a
+a
+a
+a
+
+b";
+
+    #[test]
+    fn example() {
+        let input = Day6::parse(EXAMPLE).unwrap();
+        assert_eq!(Day6::part1(&input), Some(11));
+        assert_eq!(Day6::part2(&input), Some(6));
     }
 
-    return format!("Day 6.2: sum of answers = {}", sum);
-}
\ No newline at end of file
+    #[test]
+    fn blank_lines() {
+        // whitespace only lines separate groups, repeated blank lines don't add empty groups
+        let input = Day6::parse("\nab\n \t\nb\n\n\n\nb\r\n\r\nc\n\n").unwrap();
+        assert_eq!(input, vec![vec!["ab"], vec!["b"], vec!["b"], vec!["c"]]);
+        assert_eq!(Day6::part1(&input), Some(5));
+        assert_eq!(Day6::part2(&input), Some(5));
+    }
+}
@@ -1,34 +1,37 @@
-use crate::input;
+use crate::input::{self, InputError, ParseError};
+use crate::solver::Solver;
 use std::collections::{HashMap, HashSet};
 use regex::Regex;
 
 type Bag = String;
 
-struct BagRule {
+#[derive(Clone)]
+pub struct BagRule {
     color: String,
     contains: Vec<(u32, Bag)>
 }
 
 impl BagRule {
-    fn from_string(description: &String) -> BagRule {
+    fn from_string(description: &str) -> Result<BagRule, ParseError> {
         lazy_static! {
             static ref RE_BAG_COLOR: Regex = Regex::new(
                 r"([a-zA-Z]+ [a-zA-Z]+) bags contain").unwrap();
             static ref RE_BAG_CONTAINS: Regex = Regex::new(
                 r"(?:(\d+) ([a-zA-Z]+ [a-zA-Z]+) bags?[.|,])").unwrap();
         }
-        let color = String::from(&RE_BAG_COLOR.captures(description).unwrap()[1]);
-        let contains: Vec<(u32, String)> =
-            RE_BAG_CONTAINS.captures_iter(description)
-                .map(|capture|
-                    (String::from(&capture[1]).parse::<u32>().unwrap(), String::from(&capture[2]))
-                )
-                .collect();
-
-        return BagRule {
+        let color = match RE_BAG_COLOR.captures(description) {
+            Some(capture) => String::from(&capture[1]),
+            None => return Err(ParseError::new(String::from("expected '<color> bags contain ...'")))
+        };
+        let mut contains: Vec<(u32, String)> = Vec::new();
+        for capture in RE_BAG_CONTAINS.captures_iter(description) {
+            contains.push((capture[1].parse::<u32>()?, String::from(&capture[2])));
+        }
+
+        return Ok(BagRule {
             color: color,
             contains: contains
-        }
+        });
     }
 }
 
@@ -47,14 +50,13 @@ impl BagRules {
 
     fn add_rule(&mut self, bag_rule: BagRule) {
         self.rules.insert(bag_rule.color.clone(), bag_rule);
-        ()
     }
 
-    fn add_inverse_rule(&mut self, bag_rule: BagRule) {
-        let color = bag_rule.color;
-        for (number, enclosing_color) in bag_rule.contains {
-            let list_of_enclosing_colors = self.is_contained_in.entry(enclosing_color).or_insert(Vec::new());
-            list_of_enclosing_colors.push((number, color.clone()));
+    fn add_inverse_rule(&mut self, bag_rule: &BagRule) {
+        let color = &bag_rule.color;
+        for (number, enclosing_color) in &bag_rule.contains {
+            let list_of_enclosing_colors = self.is_contained_in.entry(enclosing_color.clone()).or_default();
+            list_of_enclosing_colors.push((*number, color.clone()));
         }
     }
 
@@ -90,31 +92,67 @@ impl BagRules {
 }
 
 
-pub fn question1() -> String {
-    let filename = "input/day7.txt";
-    let input = input::lines_as::<String>(filename);
+pub struct Day7;
 
-    let mut bag_rules = BagRules::new();
-    for rule in &input {
-        let bag_rule = BagRule::from_string(rule);
-        bag_rules.add_inverse_rule(bag_rule);
+impl Solver for Day7 {
+    type Input = Vec<BagRule>;
+    type Answer1 = usize;
+    type Answer2 = u32;
+
+    fn parse(input: &str) -> Result<Vec<BagRule>, InputError> {
+        return input::lines_with(input, BagRule::from_string);
     }
 
-    let bags = bag_rules.collect_all_enclosing_bags(&String::from("shiny gold"));
-    let number_of_bags = bags.len();
+    fn part1(rules: &Vec<BagRule>) -> Option<usize> {
+        let mut bag_rules = BagRules::new();
+        for rule in rules {
+            bag_rules.add_inverse_rule(rule);
+        }
 
-    return format!("Day 7.1: number of bag colors containing a shiny gold bag = {}", number_of_bags);
-}
+        let bags = bag_rules.collect_all_enclosing_bags(&String::from("shiny gold"));
+        return Some(bags.len());
+    }
 
-pub fn question2() -> String {
-    let filename = "input/day7.txt";
-    let input = input::lines_as::<String>(filename);
+    fn part2(rules: &Vec<BagRule>) -> Option<u32> {
+        let mut bag_rules = BagRules::new();
+        for rule in rules {
+            bag_rules.add_rule(rule.clone());
+        }
+        return Some(bag_rules.number_of_required_bags(&String::from("shiny gold")));
+    }
+}
 
-    let mut bag_rules = BagRules::new();
-    for rule in &input {
-        let bag_rule = BagRule::from_string(rule);
-        bag_rules.add_rule(bag_rule);
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+light red bags contain 1 bright white bag, 2 muted yellow bags.
+dark orange bags contain 3 bright white bags, 4 muted yellow bags.
+bright white bags contain 1 shiny gold bag.
+muted yellow bags contain 2 shiny gold bags, 9 faded blue bags.
+shiny gold bags contain 1 dark olive bag, 2 vibrant plum bags.
+dark olive bags contain 3 faded blue bags, 4 dotted black bags.
+vibrant plum bags contain 5 faded blue bags, 6 dotted black bags.
+faded blue bags contain no other bags.
+dotted black bags contain no other bags.";
+
+    const NESTED: &str = "\
+shiny gold bags contain 2 dark red bags.
+dark red bags contain 2 dark orange bags.
+dark orange bags contain 2 dark yellow bags.
+dark yellow bags contain 2 dark green bags.
+dark green bags contain 2 dark blue bags.
+dark blue bags contain 2 dark violet bags.
+dark violet bags contain no other bags.";
+
+    #[test]
+    fn example() {
+        let input = Day7::parse(EXAMPLE).unwrap();
+        assert_eq!(Day7::part1(&input), Some(4));
+        assert_eq!(Day7::part2(&input), Some(32));
+
+        let nested = Day7::parse(NESTED).unwrap();
+        assert_eq!(Day7::part2(&nested), Some(126));
     }
-    let number_of_individual_bags = bag_rules.number_of_required_bags(&String::from("shiny gold"));
-    return format!("Day 7.2: number of individual bags = {}", number_of_individual_bags);
-}
\ No newline at end of file
+}
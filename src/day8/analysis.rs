@@ -0,0 +1,258 @@
+use super::{Instruction, DEFAULT_WIDTH};
+use super::cfg::{self, Cfg};
+use super::isa::{Operand, OperandKind};
+use super::vm::{VmError, Word};
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Class {
+    // never executed, whatever the flow through the program (dead code)
+    Unreachable,
+    // part of a loop
+    Cycle,
+    // every path from here leaves the program at its end
+    Terminating,
+    // reachable, but leads into a loop or out of the program
+    Reachable,
+}
+
+impl fmt::Display for Class {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Class::Unreachable => write!(f, "unreachable"),
+            Class::Cycle => write!(f, "cycle"),
+            Class::Terminating => write!(f, "terminating"),
+            Class::Reachable => write!(f, "reachable"),
+        }
+    }
+}
+
+// Patch of a single instruction that makes the program terminate
+#[derive(Debug, PartialEq)]
+pub struct Patch {
+    pub index: usize,
+    pub old: Instruction,
+    pub new: Instruction,
+    // None if the accumulator depends on values only known at runtime
+    pub accumulator: Option<Word>,
+}
+
+pub struct Analysis {
+    pub classes: Vec<Class>,
+    // every single instruction patch that makes the program terminate
+    pub patches: Vec<Patch>,
+}
+
+// Writes to the accumulator, unless it is only known at runtime
+enum Effect {
+    None,
+    Add(Word),
+    Unknown,
+}
+
+fn effect(instruction: &Instruction) -> Effect {
+    let opcode = instruction.opcode();
+    match instruction.operands() {
+        [Operand::Number(value)] if opcode.mnemonic == "acc" => Effect::Add(*value),
+        [Operand::Register(_)] if opcode.mnemonic == "acc" => Effect::Unknown,
+        // instructions store their result in a leading register operand
+        [Operand::Register(0), ..] if opcode.operands.first() == Some(&OperandKind::Register) => Effect::Unknown,
+        _ => Effect::None,
+    }
+}
+
+// Accumulator once a program, known to terminate, has left its end. Follows
+// the control flow graph from the start and adds up the `acc` instructions on
+// the way. None if the value depends on the input: at a branch or at any
+// other write to the accumulator. An error if the accumulator overflows.
+fn final_accumulator(program: &[Instruction], cfg: &Cfg) -> Result<Option<Word>, VmError> {
+    let mut accumulator: Word = 0;
+    let mut index = 0;
+    while index < program.len() {
+        match effect(&program[index]) {
+            Effect::None => (),
+            Effect::Add(value) => {
+                accumulator = Some(value)
+                    .filter(|value| DEFAULT_WIDTH.contains(*value))
+                    .and_then(|value| accumulator.checked_add(value))
+                    .filter(|value| DEFAULT_WIDTH.contains(*value))
+                    .ok_or(VmError::Overflow { ip: index })?;
+            }
+            Effect::Unknown => return Ok(None),
+        }
+        // a terminating program has no cycles, so the walk ends
+        index = match cfg.successors(index) {
+            [(next, _)] => *next,
+            _ => return Ok(None),
+        };
+    }
+    return Ok(Some(accumulator));
+}
+
+// Classifies the instructions of a program using its control flow graph and
+// finds every patching of a single instruction that repairs it, without
+// running the program.
+pub fn analyse(program: &[Instruction]) -> Analysis {
+    let cfg = Cfg::build(program);
+    let reachable = cfg.reachable(0);
+    let cycles = cfg.cycles();
+    let terminating = cfg.terminating();
+
+    let classes = (0..program.len())
+        .map(|index| {
+            if !reachable[index] {
+                Class::Unreachable
+            } else if cycles[index] {
+                Class::Cycle
+            } else if terminating[index] {
+                Class::Terminating
+            } else {
+                Class::Reachable
+            }
+        })
+        .collect();
+
+    // A patch can only help if it is reached and all of its new successors
+    // terminate. For the candidates left the patched program has to terminate
+    // from its start.
+    let mut patches = Vec::new();
+    for (index, instruction) in program.iter().enumerate() {
+        let patched = match instruction.patched() {
+            Some(patched) if reachable[index] => patched,
+            _ => continue,
+        };
+        if !cfg::leads_to_end(&cfg::successors(index, &patched, program.len()), &terminating) {
+            continue;
+        }
+
+        let mut program = program.to_vec();
+        program[index] = patched.clone();
+        let patched_cfg = Cfg::build(&program);
+        if !patched_cfg.terminating()[0] {
+            continue;
+        }
+        // the patched program fails if the accumulator overflows
+        let accumulator = match final_accumulator(&program, &patched_cfg) {
+            Ok(accumulator) => accumulator,
+            Err(_) => continue,
+        };
+        patches.push(Patch {
+            index: index,
+            old: instruction.clone(),
+            new: patched,
+            accumulator: accumulator,
+        });
+    }
+
+    return Analysis {
+        classes: classes,
+        patches: patches,
+    };
+}
+
+impl Analysis {
+    // ranges (first, last) of consecutive unreachable instructions
+    pub fn dead_code(&self) -> Vec<(usize, usize)> {
+        let mut ranges: Vec<(usize, usize)> = Vec::new();
+        for (index, class) in self.classes.iter().enumerate() {
+            if *class != Class::Unreachable {
+                continue;
+            }
+            match ranges.last_mut() {
+                Some((_, last)) if *last + 1 == index => *last = index,
+                _ => ranges.push((index, index)),
+            }
+        }
+        return ranges;
+    }
+
+    pub fn count(&self, class: Class) -> usize {
+        return self.classes.iter().filter(|c| **c == class).count();
+    }
+}
+
+impl fmt::Display for Analysis {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} instructions", self.classes.len())?;
+        for class in &[Class::Reachable, Class::Cycle, Class::Terminating, Class::Unreachable] {
+            writeln!(f, "  {:<12} {}", class.to_string(), self.count(*class))?;
+        }
+
+        let dead_code: Vec<String> = self.dead_code().iter()
+            .map(|(first, last)| match first == last {
+                true => first.to_string(),
+                false => format!("{}-{}", first, last),
+            })
+            .collect();
+        if !dead_code.is_empty() {
+            writeln!(f, "dead code: {}", dead_code.join(", "))?;
+        }
+
+        match self.patches.len() {
+            0 => writeln!(f, "no single instruction patch makes the program terminate")?,
+            n => writeln!(f, "{} patch(es) make the program terminate:", n)?,
+        }
+        for patch in &self.patches {
+            let accumulator = match patch.accumulator {
+                Some(accumulator) => accumulator.to_string(),
+                None => String::from("unknown"),
+            };
+            writeln!(f, "  {:>4}: '{}' -> '{}', accumulator {}", patch.index, patch.old, patch.new, accumulator)?;
+        }
+        return Ok(());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day8::{Day8, EXAMPLE};
+    use crate::solver::Solver;
+
+    #[test]
+    fn classes() {
+        let analysis = analyse(&Day8::parse(EXAMPLE).unwrap());
+        use Class::*;
+        assert_eq!(analysis.classes, vec![Reachable, Cycle, Cycle, Cycle, Cycle, Unreachable, Cycle, Cycle, Unreachable]);
+        assert_eq!(analysis.dead_code(), vec![(5, 5), (8, 8)]);
+    }
+
+    #[test]
+    fn patches() {
+        let analysis = analyse(&Day8::parse(EXAMPLE).unwrap());
+        let patches: Vec<(usize, String, Option<i128>)> = analysis.patches.iter()
+            .map(|patch| (patch.index, patch.new.to_string(), patch.accumulator))
+            .collect();
+        assert_eq!(patches, vec![(7, String::from("nop -4"), Some(8))]);
+
+        // every patch of a reached instruction keeps a terminating program terminating
+        let analysis = analyse(&Day8::parse("acc +1\nacc +2\njmp +2\nacc +4").unwrap());
+        assert_eq!(analysis.classes, vec![Class::Terminating, Class::Terminating, Class::Terminating, Class::Unreachable]);
+        let patches: Vec<(usize, Option<i128>)> = analysis.patches.iter().map(|patch| (patch.index, patch.accumulator)).collect();
+        assert_eq!(patches, vec![(0, Some(2)), (1, Some(1)), (2, Some(7))]);
+
+        // the accumulator would overflow, or depends on the input
+        let analysis = analyse(&Day8::parse("acc +9223372036854775807\njmp +0\nacc +1").unwrap());
+        assert_eq!(analysis.patches.len(), 0);
+        let analysis = analyse(&Day8::parse("in acc\njmp +0\nacc +1").unwrap());
+        let patches: Vec<(usize, Option<i128>)> = analysis.patches.iter().map(|patch| (patch.index, patch.accumulator)).collect();
+        assert_eq!(patches, vec![(1, None)]);
+    }
+
+    #[test]
+    fn register_targets() {
+        use Class::*;
+        // the conditional jump falls through, so the code after it is live
+        let analysis = analyse(&Day8::parse("in r1\njz r1 r2\nacc +1\nacc +2").unwrap());
+        assert_eq!(analysis.classes, vec![Reachable, Reachable, Terminating, Terminating]);
+        assert!(analysis.dead_code().is_empty());
+
+        // a jump by a register value may go anywhere
+        let analysis = analyse(&Day8::parse("in r2\njmp r2\nacc +1").unwrap());
+        assert_eq!(analysis.classes, vec![Reachable, Reachable, Terminating]);
+        let patches: Vec<(usize, String, Option<i128>)> = analysis.patches.iter()
+            .map(|patch| (patch.index, patch.new.to_string(), patch.accumulator))
+            .collect();
+        assert_eq!(patches, vec![(1, String::from("nop r2"), Some(1))]);
+    }
+}
@@ -0,0 +1,184 @@
+use super::Instruction;
+use super::isa::{self, Operand, REGISTERS};
+use super::vm::Word;
+use crate::input::{InputError, ParseError};
+use std::collections::{BTreeSet, HashMap};
+
+// label of the instruction at the given index, `end` marks the first index after the program
+fn label(index: usize, len: usize) -> String {
+    if index == len {
+        return String::from("end");
+    }
+    return format!("L{}", index);
+}
+
+// Converts a program back into the puzzle format, one `acc +1` instruction per line.
+pub fn to_text(program: &[Instruction]) -> String {
+    let mut text = String::new();
+    for instruction in program {
+        text.push_str(&format!("{}\n", instruction));
+    }
+    return text;
+}
+
+// Disassembles a program into label based source. Every jump target (also of
+// `nop`, since it turns into a jump once patched) gets a label, and the offset
+// is replaced by that label. Targets outside of the program keep their offset.
+pub fn disassemble(program: &[Instruction]) -> String {
+    let len = program.len();
+    let targets: BTreeSet<usize> = program.iter()
+        .enumerate()
+        .filter_map(|(index, instruction)| instruction.jump_target(index, len))
+        .collect();
+
+    let mut source = String::new();
+    for (index, instruction) in program.iter().enumerate() {
+        if targets.contains(&index) {
+            source.push_str(&format!("{}:\n", label(index, len)));
+        }
+        let mut line = String::from(instruction.opcode().mnemonic);
+        for (position, operand) in instruction.operands().iter().enumerate() {
+            let target = instruction.jump_target(index, len);
+            match target {
+                Some(target) if instruction.opcode().target == Some(position) => {
+                    line.push_str(&format!(" {}", label(target, len)));
+                }
+                _ => line.push_str(&format!(" {}", operand)),
+            }
+        }
+        source.push_str(&format!("    {:<12} ; {}\n", line, index));
+    }
+    if targets.contains(&len) {
+        source.push_str(&format!("{}:\n", label(len, len)));
+    }
+    return source;
+}
+
+// labels must not shadow register names
+fn is_label(name: &str) -> bool {
+    let mut chars = name.chars();
+    let valid = match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' => chars.all(|c| c.is_ascii_alphanumeric() || c == '_'),
+        _ => false,
+    };
+    return valid && !REGISTERS.contains(&name);
+}
+
+fn error(line: usize, text: &str, message: String) -> InputError {
+    return InputError::parse(line, text, ParseError::new(message));
+}
+
+// Assembles label based source into a program. Supported syntax:
+//
+//   ; comments start with ';' or '#'
+//   start:          labels end with ':' and may be followed by an instruction
+//       acc +3
+//       jmp start   the jump offset of an instruction can be a label
+//       jz r1 start
+//   done: nop +0
+pub fn assemble(source: &str) -> Result<Vec<Instruction>, InputError> {
+    let mut labels: HashMap<String, usize> = HashMap::new();
+    // (line number, line, mnemonic, operands)
+    let mut statements = Vec::new();
+
+    for (idx, line) in source.lines().enumerate() {
+        let mut code = line;
+        if let Some(pos) = code.find([';', '#']) {
+            code = &code[..pos];
+        }
+        let mut code = code.trim();
+
+        if let Some((name, rest)) = code.split_once(':') {
+            let name = name.trim();
+            if !is_label(name) {
+                return Err(error(idx + 1, line, format!("invalid label '{}'", name)));
+            }
+            if labels.insert(name.to_string(), statements.len()).is_some() {
+                return Err(error(idx + 1, line, format!("duplicate label '{}'", name)));
+            }
+            code = rest.trim();
+        }
+
+        let mut parts = code.split_whitespace();
+        if let Some(mnemonic) = parts.next() {
+            statements.push((idx + 1, line, mnemonic, parts.collect::<Vec<&str>>()));
+        }
+    }
+
+    let mut program = Vec::new();
+    for (index, (line_number, line, mnemonic, operands)) in statements.iter().enumerate() {
+        let opcode = isa::lookup(mnemonic)
+            .ok_or_else(|| error(*line_number, line, format!("unknown instruction '{}'", mnemonic)))?;
+        if operands.len() != opcode.operands.len() {
+            let message = format!("{} expects {} operand(s), found {}", mnemonic, opcode.operands.len(), operands.len());
+            return Err(error(*line_number, line, message));
+        }
+
+        let mut parsed = Vec::new();
+        for (position, (operand, kind)) in operands.iter().zip(opcode.operands).enumerate() {
+            if opcode.target == Some(position) && is_label(operand) {
+                let target = labels.get(*operand)
+                    .ok_or_else(|| error(*line_number, line, format!("unknown label '{}'", operand)))?;
+                parsed.push(Operand::Number(*target as Word - index as Word));
+            } else {
+                parsed.push(Operand::parse(operand, *kind).map_err(|e| InputError::parse(*line_number, line, e))?);
+            }
+        }
+        program.push(Instruction::new(opcode, parsed));
+    }
+    return Ok(program);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day8::{Day8, EXAMPLE};
+    use crate::solver::Solver;
+
+    #[test]
+    fn disassemble_uses_labels() {
+        let program = Day8::parse(EXAMPLE).unwrap();
+        let source = disassemble(&program);
+        assert!(source.contains("L0:\n    nop L0"));
+        assert!(source.contains("    jmp L6"));
+        assert!(source.contains("L1:\n    acc +1"));
+    }
+
+    #[test]
+    fn roundtrip() {
+        let program = Day8::parse(EXAMPLE).unwrap();
+        let assembled = assemble(&disassemble(&program)).unwrap();
+        assert_eq!(to_text(&assembled), format!("{}\n", EXAMPLE));
+    }
+
+    #[test]
+    fn assemble_source() {
+        let source = "\
+            ; count to three
+            start: acc +1
+                   jmp next # skip
+                   acc -10
+            next:  jmp end
+            end:";
+        let program = assemble(source).unwrap();
+        assert_eq!(to_text(&program), "acc +1\njmp +2\nacc -10\njmp +1\n");
+    }
+
+    #[test]
+    fn assemble_errors() {
+        let error = assemble("acc +1\njmp nowhere").unwrap_err();
+        assert_eq!(error.to_string(), "<input>:2: unknown label 'nowhere' (in 'jmp nowhere')");
+        assert!(assemble("a: nop +0\na: nop +0").is_err());
+        assert!(assemble("mul +2").is_err());
+        assert!(assemble("acc a\na: nop +0").is_err());
+        assert!(assemble("acc: nop +0").is_err());
+    }
+
+    #[test]
+    fn conditional_jumps_use_labels() {
+        let source = "in r1\nloop: jz r1 done\nadd acc r1\nsub r1 +1\njmp loop\ndone: out acc\n";
+        let program = assemble(source).unwrap();
+        assert_eq!(to_text(&program), "in r1\njz r1 +4\nadd acc r1\nsub r1 +1\njmp -3\nout acc\n");
+        assert_eq!(assemble(&disassemble(&program)).unwrap(), program);
+    }
+}
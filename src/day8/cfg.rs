@@ -0,0 +1,340 @@
+use super::{Computer, Instruction};
+use super::isa::Operand;
+use super::vm::Word;
+use std::collections::VecDeque;
+
+// How control gets from an instruction to its successor
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Flow {
+    FallThrough,
+    Jump,
+}
+
+// Where control can go from an instruction, as far as it is known before
+// running the program
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Target {
+    // an instruction, or the end of the program at index `len`
+    Node(usize),
+    // a fixed jump before the start or past the end, which fails at runtime
+    OutOfBounds,
+    // a jump by a register value, which may go anywhere
+    Unknown,
+}
+
+// Control flow graph of a program. Nodes are the instruction indices, plus
+// the node `len` for leaving the program at its end (termination). Jumps
+// whose target is not a node are kept apart from the edges.
+pub struct Cfg {
+    len: usize,
+    successors: Vec<Vec<(usize, Flow)>>,
+    // instructions that may jump out of bounds or to an unknown target
+    escapes: Vec<Vec<Target>>,
+}
+
+impl Cfg {
+    pub fn build(program: &[Instruction]) -> Cfg {
+        let len = program.len();
+        let mut all_successors = Vec::with_capacity(len);
+        let mut all_escapes = Vec::with_capacity(len);
+        for (index, instruction) in program.iter().enumerate() {
+            let mut nodes = Vec::new();
+            let mut escapes = Vec::new();
+            for (target, flow) in successors(index, instruction, len) {
+                match target {
+                    Target::Node(node) => nodes.push((node, flow)),
+                    _ => escapes.push(target),
+                }
+            }
+            all_successors.push(nodes);
+            all_escapes.push(escapes);
+        }
+        return Cfg {
+            len: len,
+            successors: all_successors,
+            escapes: all_escapes,
+        };
+    }
+
+    // node for leaving the program at its end
+    pub fn exit(&self) -> usize {
+        return self.len;
+    }
+
+    pub fn successors(&self, index: usize) -> &[(usize, Flow)] {
+        return &self.successors[index];
+    }
+
+    // Whether each instruction is guaranteed to reach the end of the program,
+    // computed backwards from the end: an instruction terminates once all of
+    // its successors do. An instruction that may jump out of bounds or to an
+    // unknown target never does. Runs in linear time in the number of edges.
+    pub fn terminating(&self) -> Vec<bool> {
+        let mut predecessors = vec![Vec::new(); self.len + 1];
+        let mut remaining = Vec::with_capacity(self.len);
+        for (index, successors) in self.successors.iter().enumerate() {
+            for (successor, _) in successors {
+                predecessors[*successor].push(index);
+            }
+            // escapes are never resolved, so the count stays above zero
+            remaining.push(successors.len() + self.escapes[index].len());
+        }
+
+        let mut terminating = vec![false; self.len + 1];
+        terminating[self.len] = true;
+        let mut queue = VecDeque::new();
+        queue.push_back(self.len);
+
+        while let Some(node) = queue.pop_front() {
+            for predecessor in &predecessors[node] {
+                remaining[*predecessor] -= 1;
+                if remaining[*predecessor] == 0 {
+                    terminating[*predecessor] = true;
+                    queue.push_back(*predecessor);
+                }
+            }
+        }
+        terminating.truncate(self.len);
+        return terminating;
+    }
+
+    // Instructions reachable from the given one, following every edge. A jump
+    // to an unknown target may go anywhere, so once one is reached every
+    // instruction is.
+    pub fn reachable(&self, from: usize) -> Vec<bool> {
+        let mut reachable = vec![false; self.len + 1];
+        let mut stack = vec![from];
+        reachable[from] = true;
+        while let Some(node) = stack.pop() {
+            if self.escapes.get(node).is_some_and(|escapes| escapes.contains(&Target::Unknown)) {
+                return vec![true; self.len];
+            }
+            for (successor, _) in self.edges(node) {
+                if !reachable[*successor] {
+                    reachable[*successor] = true;
+                    stack.push(*successor);
+                }
+            }
+        }
+        reachable.truncate(self.len);
+        return reachable;
+    }
+
+    // Whether each instruction lies on a cycle, using the strongly connected
+    // components of the graph (Kosaraju, with explicit stacks). Only edges
+    // known statically count, a loop through an unknown target is not found.
+    pub fn cycles(&self) -> Vec<bool> {
+        let nodes = self.len + 1;
+
+        // first pass: nodes ordered by the time their depth first search finished
+        let mut order = Vec::with_capacity(nodes);
+        let mut seen = vec![false; nodes];
+        for start in 0..nodes {
+            if seen[start] {
+                continue;
+            }
+            seen[start] = true;
+            let mut stack = vec![(start, 0)];
+            while let Some((node, next)) = stack.pop() {
+                match self.edges(node).get(next) {
+                    Some((successor, _)) => {
+                        stack.push((node, next + 1));
+                        if !seen[*successor] {
+                            seen[*successor] = true;
+                            stack.push((*successor, 0));
+                        }
+                    }
+                    None => order.push(node),
+                }
+            }
+        }
+
+        // second pass: components of the reversed graph, in reverse finishing order
+        let mut predecessors = vec![Vec::new(); nodes];
+        for node in 0..nodes {
+            for (successor, _) in self.edges(node) {
+                predecessors[*successor].push(node);
+            }
+        }
+        let mut component = vec![usize::MAX; nodes];
+        let mut sizes = Vec::new();
+        for start in order.into_iter().rev() {
+            if component[start] != usize::MAX {
+                continue;
+            }
+            let id = sizes.len();
+            sizes.push(0);
+            component[start] = id;
+            let mut stack = vec![start];
+            while let Some(node) = stack.pop() {
+                sizes[id] += 1;
+                for predecessor in &predecessors[node] {
+                    if component[*predecessor] == usize::MAX {
+                        component[*predecessor] = id;
+                        stack.push(*predecessor);
+                    }
+                }
+            }
+        }
+
+        return (0..self.len)
+            .map(|node| sizes[component[node]] > 1 || self.edges(node).iter().any(|(successor, _)| *successor == node))
+            .collect();
+    }
+
+    // outgoing edges of a node, the exit has none
+    fn edges(&self, node: usize) -> &[(usize, Flow)] {
+        match self.successors.get(node) {
+            Some(successors) => successors,
+            None => &[],
+        }
+    }
+}
+
+// Every way control can leave an instruction. Never empty: an unconditional
+// jump whose target isn't known statically has an out of bounds or unknown
+// target instead.
+pub fn successors(index: usize, instruction: &Instruction, len: usize) -> Vec<(Target, Flow)> {
+    let opcode = instruction.opcode();
+    let mut successors = Vec::new();
+    // everything but an unconditional jump can fall through
+    if opcode.mnemonic != "jmp" {
+        successors.push((Target::Node(index + 1), Flow::FallThrough));
+    }
+    // nop ignores its offset until it is flipped
+    if opcode.mnemonic == "nop" {
+        return successors;
+    }
+    if let Some(position) = opcode.target {
+        let target = match instruction.operands()[position] {
+            Operand::Number(_) => instruction.jump_target(index, len).map_or(Target::OutOfBounds, Target::Node),
+            Operand::Register(_) => Target::Unknown,
+        };
+        successors.push((target, Flow::Jump));
+    }
+    successors.dedup_by_key(|(target, _)| *target);
+    return successors;
+}
+
+// Whether control is guaranteed to reach the end of the program from every
+// successor, given which instructions terminate (one entry per instruction).
+// False for an instruction without successors, and for jumps out of bounds or
+// to unknown targets.
+pub fn leads_to_end(successors: &[(Target, Flow)], terminating: &[bool]) -> bool {
+    if successors.is_empty() {
+        return false;
+    }
+    return successors.iter().all(|(target, _)| match target {
+        Target::Node(node) => *node == terminating.len() || terminating[*node],
+        Target::OutOfBounds | Target::Unknown => false,
+    });
+}
+
+// Instructions executed before the program terminates or an instruction is
+// about to run a second time, in execution order
+pub fn executed(program: &[Instruction]) -> (Vec<usize>, bool) {
+    let mut computer = Computer::load_program(program.to_vec());
+    let mut visited = vec![false; program.len()];
+    let mut path = Vec::new();
+
+    while let Some(ip) = computer.current() {
+        if visited[ip] {
+            break;
+        }
+        visited[ip] = true;
+        path.push(ip);
+        computer.next();
+    }
+    return (path, computer.is_terminated());
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Repair {
+    pub index: usize,
+    pub old: Instruction,
+    pub new: Instruction,
+    pub accumulator: Word,
+}
+
+// Finds the jmp/nop flip that makes the program terminate. The flip has to
+// happen on the executed path and lead into an instruction that terminates,
+// so the search only needs the terminating set and a single run. Returns None
+// if the program already terminates or no single flip repairs it.
+pub fn find_repair(program: &[Instruction]) -> Option<Repair> {
+    let (path, terminates) = executed(program);
+    if terminates {
+        return None;
+    }
+
+    let terminating = Cfg::build(program).terminating();
+    let len = program.len();
+    for index in path {
+        let flipped = match program[index].flipped() {
+            Some(flipped) => flipped,
+            None => continue,
+        };
+        if !leads_to_end(&successors(index, &flipped, len), &terminating) {
+            continue;
+        }
+
+        let mut patched = program.to_vec();
+        patched[index] = flipped.clone();
+        // the flip terminates statically, but can still overflow at runtime
+        let accumulator = match Computer::load_program(patched).run() {
+            Some(Ok(accumulator)) => accumulator,
+            _ => continue,
+        };
+        return Some(Repair {
+            index: index,
+            old: program[index].clone(),
+            new: flipped,
+            accumulator: accumulator,
+        });
+    }
+    return None;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day8::{Day8, EXAMPLE};
+    use crate::solver::Solver;
+
+    #[test]
+    fn terminating_instructions() {
+        let program = Day8::parse(EXAMPLE).unwrap();
+        let terminating = Cfg::build(&program).terminating();
+        assert_eq!(terminating, vec![false, false, false, false, false, false, false, false, true]);
+    }
+
+    #[test]
+    fn repair() {
+        let program = Day8::parse(EXAMPLE).unwrap();
+        let repair = find_repair(&program).unwrap();
+        assert_eq!(repair.index, 7);
+        assert_eq!((repair.old.to_string(), repair.new.to_string()), (String::from("jmp -4"), String::from("nop -4")));
+        assert_eq!(repair.accumulator, 8);
+
+        assert_eq!(find_repair(&Day8::parse("acc +1\nnop +0").unwrap()), None);
+    }
+
+    #[test]
+    fn targets_outside_of_the_graph() {
+        let program = Day8::parse("jmp +5\njmp r1\njz r1 r2").unwrap();
+        assert_eq!(successors(0, &program[0], 3), vec![(Target::OutOfBounds, Flow::Jump)]);
+        assert_eq!(successors(1, &program[1], 3), vec![(Target::Unknown, Flow::Jump)]);
+        assert_eq!(successors(2, &program[2], 3), vec![(Target::Node(3), Flow::FallThrough), (Target::Unknown, Flow::Jump)]);
+
+        // none of them is known to reach the end, even though all instructions would terminate
+        let terminating = vec![true; 3];
+        assert!(!leads_to_end(&[], &terminating));
+        for (index, instruction) in program.iter().enumerate() {
+            assert!(!leads_to_end(&successors(index, instruction, 3), &terminating));
+        }
+        assert_eq!(Cfg::build(&program).terminating(), vec![false, false, false]);
+
+        // flipping the nop would jump out of bounds, flipping the jmp repairs the program
+        let repair = find_repair(&Day8::parse("nop +5\njmp -1").unwrap()).unwrap();
+        assert_eq!((repair.index, repair.new.to_string()), (1, String::from("nop -1")));
+    }
+}
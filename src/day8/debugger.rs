@@ -0,0 +1,525 @@
+use super::{Computer, Instruction};
+use super::isa::REGISTERS;
+use super::vm::{StepResult, VmError, Word};
+use super::asm;
+use super::trace;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{BufRead, Write};
+
+const HELP: &str = "\
+commands:
+  step [n]          execute the next n instructions (default 1)
+  run               run until a breakpoint, an infinite loop or termination
+  break <ip>        stop before the instruction at ip is executed
+  break acc <n>     stop once the accumulator equals n
+  delete <id>       remove a breakpoint
+  watch <expr>      print an expression after every stop, e.g. `acc * 2` or `ip == 7`
+  unwatch <id>      remove a watch expression
+  info              show ip, accumulator, breakpoints and watches
+  list [n]          disassemble n instructions around ip (default 5)
+  visited           show the instructions executed so far
+  disasm            disassemble the whole program with labels
+  input <n>...      queue values for the `in` instruction
+  output            show the values written by `out`
+  back [n]          undo the last n steps (default 1)
+  trace [n]         show the last n recorded steps (default 10)
+  trace save <file> write the recorded steps to a file, one JSON object per line
+  reset             restart the program
+  help              show this help
+  quit              leave the debugger";
+
+#[derive(Debug, Clone, PartialEq)]
+enum Breakpoint {
+    Ip(usize),
+    Accumulator(Word),
+}
+
+// Why `run` stopped
+#[derive(Debug, PartialEq)]
+enum Stop {
+    Breakpoint(usize),
+    Loop(usize),
+    Terminated,
+    Error(VmError),
+}
+
+// Interactive debugger around a `Computer`. Commands are executed through
+// `execute`, which returns the text to show to the user.
+pub struct Debugger {
+    program: Vec<Instruction>,
+    computer: Computer,
+    // how often each instruction was executed
+    visited: BTreeMap<usize, usize>,
+    steps: usize,
+    trace_size: usize,
+    breakpoints: Vec<Breakpoint>,
+    watches: Vec<Expr>,
+}
+
+impl Debugger {
+    // keeps the last `trace_size` steps around for stepping backwards
+    pub fn new(program: Vec<Instruction>, trace_size: usize) -> Debugger {
+        let mut computer = Computer::load_program(program.clone());
+        computer.trace(trace_size);
+        return Debugger {
+            computer: computer,
+            program: program,
+            visited: BTreeMap::new(),
+            steps: 0,
+            trace_size: trace_size,
+            breakpoints: Vec::new(),
+            watches: Vec::new(),
+        };
+    }
+
+    fn state(&self) -> String {
+        let instruction = match self.computer.current() {
+            Some(ip) => self.computer.program[ip].to_string(),
+            None if self.computer.is_terminated() => String::from("<end of program>"),
+            None => String::from("<out of bounds>"),
+        };
+        let registers: Vec<String> = REGISTERS.iter()
+            .zip(self.computer.registers.iter())
+            .map(|(name, value)| format!("{} = {}", name, value))
+            .collect();
+        let mut state = format!("ip = {}  {}  steps = {}  next: {}",
+            self.computer.ip, registers.join("  "), self.steps, instruction);
+        for (id, watch) in self.watches.iter().enumerate() {
+            state.push_str(&format!("\n  watch {}: {} = {}", id, watch, watch.eval(self)));
+        }
+        return state;
+    }
+
+    // executes a single instruction, failed instructions don't count as a step
+    fn step(&mut self) -> StepResult {
+        let ip = match self.computer.current() {
+            Some(ip) => ip,
+            None => return StepResult::Halted,
+        };
+        let result = self.computer.next();
+        if let StepResult::Error(_) = result {
+            return result;
+        }
+        *self.visited.entry(ip).or_insert(0) += 1;
+        self.steps += 1;
+        return result;
+    }
+
+    // accumulator breakpoints only trigger when the value changes to the watched one
+    fn hit_breakpoint(&self, previous_acc: Word) -> Option<usize> {
+        return self.breakpoints.iter().position(|b| match b {
+            Breakpoint::Ip(ip) => self.computer.current() == Some(*ip),
+            Breakpoint::Accumulator(value) => {
+                self.computer.accumulator() == *value && previous_acc != *value
+            }
+        });
+    }
+
+    fn run(&mut self) -> Stop {
+        loop {
+            if let Some(ip) = self.computer.current() {
+                if self.visited.contains_key(&ip) {
+                    return Stop::Loop(ip);
+                }
+            }
+            let acc = self.computer.accumulator();
+            match self.step() {
+                StepResult::Continue => (),
+                StepResult::Halted => return Stop::Terminated,
+                StepResult::Error(error) => return Stop::Error(error),
+            }
+            if let Some(id) = self.hit_breakpoint(acc) {
+                return Stop::Breakpoint(id);
+            }
+        }
+    }
+
+    fn list(&self, context: usize) -> String {
+        let ip = self.computer.ip;
+        let start = ip.saturating_sub(context / 2);
+        let end = (start + context).min(self.program.len());
+
+        let mut lines = Vec::new();
+        for index in start..end {
+            let marker = if index == ip { "=>" } else { "  " };
+            let breakpoint = if self.breakpoints.contains(&Breakpoint::Ip(index)) { "*" } else { " " };
+            let visited = if self.visited.contains_key(&index) { "v" } else { " " };
+            lines.push(format!("{}{}{} {:>4}  {}", marker, breakpoint, visited, index, self.computer.program[index]));
+        }
+        return lines.join("\n");
+    }
+
+    // Executes a single command. Returns None if the debugger should quit.
+    pub fn execute(&mut self, command: &str) -> Option<Result<String, String>> {
+        let words: Vec<&str> = command.split_whitespace().collect();
+        let result = match words.as_slice() {
+            [] => Ok(String::new()),
+            ["quit"] | ["q"] | ["exit"] => return None,
+            ["help"] | ["h"] => Ok(String::from(HELP)),
+            ["step"] | ["s"] => self.execute_step(1),
+            ["step", n] | ["s", n] => parse_number(n).and_then(|n| {
+                if n < 1 {
+                    return Err(format!("expected a positive number of steps, found {}", n));
+                }
+                self.execute_step(n as usize)
+            }),
+            ["run"] | ["r"] | ["continue"] | ["c"] => {
+                let reason = match self.run() {
+                    Stop::Breakpoint(id) => format!("breakpoint {} hit", id),
+                    Stop::Loop(ip) => format!("infinite loop: instruction {} is about to run a second time", ip),
+                    Stop::Terminated => String::from("program terminated"),
+                    Stop::Error(error) => format!("stopped: {}", error),
+                };
+                Ok(format!("{}\n{}", reason, self.state()))
+            }
+            ["break", "acc", value] | ["b", "acc", value] => parse_number(value).map(|value| {
+                self.breakpoints.push(Breakpoint::Accumulator(value));
+                format!("breakpoint {} at acc == {}", self.breakpoints.len() - 1, value)
+            }),
+            ["break", ip] | ["b", ip] => parse_number(ip).and_then(|ip| {
+                if ip < 0 || ip as usize >= self.program.len() {
+                    return Err(format!("no instruction at {}", ip));
+                }
+                self.breakpoints.push(Breakpoint::Ip(ip as usize));
+                Ok(format!("breakpoint {} at ip {}", self.breakpoints.len() - 1, ip))
+            }),
+            ["delete", id] | ["d", id] => parse_number(id).and_then(|id| {
+                if id < 0 || id as usize >= self.breakpoints.len() {
+                    return Err(format!("no breakpoint {}", id));
+                }
+                self.breakpoints.remove(id as usize);
+                Ok(format!("deleted breakpoint {}", id))
+            }),
+            ["watch", ..] | ["w", ..] => Expr::parse(&words[1..]).map(|expr| {
+                let output = format!("watch {}: {} = {}", self.watches.len(), expr, expr.eval(self));
+                self.watches.push(expr);
+                output
+            }),
+            ["unwatch", id] => parse_number(id).and_then(|id| {
+                if id < 0 || id as usize >= self.watches.len() {
+                    return Err(format!("no watch {}", id));
+                }
+                self.watches.remove(id as usize);
+                Ok(format!("removed watch {}", id))
+            }),
+            ["info"] | ["i"] => {
+                let mut info = self.state();
+                for (id, breakpoint) in self.breakpoints.iter().enumerate() {
+                    match breakpoint {
+                        Breakpoint::Ip(ip) => info.push_str(&format!("\n  breakpoint {}: ip {}", id, ip)),
+                        Breakpoint::Accumulator(value) => info.push_str(&format!("\n  breakpoint {}: acc == {}", id, value)),
+                    }
+                }
+                Ok(info)
+            }
+            ["list"] | ["l"] => Ok(self.list(5)),
+            ["list", n] | ["l", n] => parse_number(n).map(|n| self.list(n.max(1) as usize)),
+            ["visited"] | ["v"] => {
+                let visited: Vec<String> = self.visited.keys().map(|ip| ip.to_string()).collect();
+                Ok(format!("{} of {} instructions visited: {}", self.visited.len(), self.program.len(), visited.join(" ")))
+            }
+            ["disasm"] => Ok(asm::disassemble(&self.program)),
+            ["input", values @ ..] if !values.is_empty() => {
+                values.iter()
+                    .map(|value| parse_number(value))
+                    .collect::<Result<Vec<Word>, String>>()
+                    .map(|values| {
+                        self.computer.input.extend(values);
+                        format!("{} value(s) queued", self.computer.input.len())
+                    })
+            }
+            ["output"] => {
+                let output: Vec<String> = self.computer.output.iter().map(|v| v.to_string()).collect();
+                Ok(output.join(" "))
+            }
+            ["back"] | ["reverse"] => self.execute_back(1),
+            ["back", n] | ["reverse", n] => parse_number(n).and_then(|n| {
+                if n < 1 {
+                    return Err(format!("expected a positive number of steps, found {}", n));
+                }
+                self.execute_back(n as usize)
+            }),
+            ["trace"] => Ok(self.trace(10)),
+            ["trace", "save", file] => {
+                let jsonl = self.computer.tracer.as_ref().map(|tracer| tracer.to_jsonl()).unwrap_or_default();
+                fs::write(file, jsonl)
+                    .map(|_| format!("trace written to {}", file))
+                    .map_err(|e| format!("failed to write {}: {}", file, e))
+            }
+            ["trace", n] => parse_number(n).map(|n| self.trace(n.max(0) as usize)),
+            ["reset"] => {
+                self.computer = Computer::load_program(self.program.clone());
+                self.computer.trace(self.trace_size);
+                self.visited.clear();
+                self.steps = 0;
+                Ok(self.state())
+            }
+            _ => Err(format!("unknown command '{}', try 'help'", command.trim())),
+        };
+        return Some(result);
+    }
+
+    fn execute_back(&mut self, n: usize) -> Result<String, String> {
+        for taken in 0..n {
+            if !self.computer.step_back() {
+                return Ok(format!("no earlier steps recorded, went back {} step(s)\n{}", taken, self.state()));
+            }
+            // the undone step executed the instruction the computer is back at
+            if let Some(ip) = self.computer.current() {
+                if let Some(count) = self.visited.get_mut(&ip) {
+                    *count -= 1;
+                    if *count == 0 {
+                        self.visited.remove(&ip);
+                    }
+                }
+            }
+            self.steps -= 1;
+        }
+        return Ok(self.state());
+    }
+
+    // the last n recorded steps, oldest first
+    fn trace(&self, n: usize) -> String {
+        let steps: Vec<&trace::Step> = match self.computer.tracer.as_ref() {
+            Some(tracer) => tracer.steps().collect(),
+            None => Vec::new(),
+        };
+        let lines: Vec<String> = steps[steps.len().saturating_sub(n)..].iter()
+            .map(|step| format!("{:>6}  {:>4}  {:<12} acc {} -> {}", step.number, step.ip, step.instruction.to_string(), step.before[0], step.after[0]))
+            .collect();
+        return lines.join("\n");
+    }
+
+    fn execute_step(&mut self, n: usize) -> Result<String, String> {
+        for _ in 0..n {
+            let acc = self.computer.accumulator();
+            match self.step() {
+                StepResult::Continue => (),
+                StepResult::Halted => return Ok(format!("program terminated\n{}", self.state())),
+                StepResult::Error(error) => return Ok(format!("stopped: {}\n{}", error, self.state())),
+            }
+            if let Some(id) = self.hit_breakpoint(acc) {
+                return Ok(format!("breakpoint {} hit\n{}", id, self.state()));
+            }
+        }
+        return Ok(self.state());
+    }
+
+    // reads commands line by line until `quit` or the end of the input
+    pub fn repl<R: BufRead, W: Write>(&mut self, input: R, output: &mut W) -> std::io::Result<()> {
+        writeln!(output, "{} instructions loaded, type 'help' for a list of commands", self.program.len())?;
+        writeln!(output, "{}", self.state())?;
+        write!(output, "(dbg) ")?;
+        output.flush()?;
+
+        for line in input.lines() {
+            match self.execute(&line?) {
+                None => return Ok(()),
+                Some(Ok(text)) => {
+                    if !text.is_empty() {
+                        writeln!(output, "{}", text)?;
+                    }
+                }
+                Some(Err(message)) => writeln!(output, "error: {}", message)?,
+            }
+            write!(output, "(dbg) ")?;
+            output.flush()?;
+        }
+        writeln!(output)?;
+        return Ok(());
+    }
+}
+
+fn parse_number(value: &str) -> Result<Word, String> {
+    return value.parse::<Word>().map_err(|_| format!("expected a number, found '{}'", value));
+}
+
+// Watch expressions: integers, registers, `ip` and `steps`, combined with
+// `+ - *` and the comparisons `== != < <= > >=` (which evaluate to 0 or 1).
+// Operators need to be separated by spaces and are evaluated with the usual
+// precedence.
+#[derive(Debug, Clone)]
+enum Expr {
+    Number(Word),
+    Register(usize),
+    Ip,
+    Steps,
+    Binary(Box<Expr>, String, Box<Expr>),
+}
+
+impl Expr {
+    fn parse(tokens: &[&str]) -> Result<Expr, String> {
+        if tokens.is_empty() {
+            return Err(String::from("missing expression"));
+        }
+        let mut pos = 0;
+        let expr = Expr::parse_level(tokens, &mut pos, 0)?;
+        if pos != tokens.len() {
+            return Err(format!("unexpected '{}'", tokens[pos]));
+        }
+        return Ok(expr);
+    }
+
+    fn parse_level(tokens: &[&str], pos: &mut usize, level: usize) -> Result<Expr, String> {
+        const LEVELS: [&[&str]; 3] = [&["==", "!=", "<", "<=", ">", ">="], &["+", "-"], &["*"]];
+        if level == LEVELS.len() {
+            return Expr::parse_operand(tokens, pos);
+        }
+
+        let mut lhs = Expr::parse_level(tokens, pos, level + 1)?;
+        while *pos < tokens.len() && LEVELS[level].contains(&tokens[*pos]) {
+            let op = tokens[*pos].to_string();
+            *pos += 1;
+            let rhs = Expr::parse_level(tokens, pos, level + 1)?;
+            lhs = Expr::Binary(Box::new(lhs), op, Box::new(rhs));
+        }
+        return Ok(lhs);
+    }
+
+    fn parse_operand(tokens: &[&str], pos: &mut usize) -> Result<Expr, String> {
+        let token = tokens.get(*pos).ok_or_else(|| String::from("incomplete expression"))?;
+        *pos += 1;
+        return match *token {
+            name if REGISTERS.contains(&name) => {
+                Ok(Expr::Register(REGISTERS.iter().position(|r| *r == name).unwrap()))
+            }
+            "ip" => Ok(Expr::Ip),
+            "steps" => Ok(Expr::Steps),
+            number => parse_number(number).map(Expr::Number),
+        };
+    }
+
+    fn eval(&self, debugger: &Debugger) -> Word {
+        return match self {
+            Expr::Number(value) => *value,
+            Expr::Register(register) => debugger.computer.registers[*register] as Word,
+            Expr::Ip => debugger.computer.ip as Word,
+            Expr::Steps => debugger.steps as Word,
+            Expr::Binary(lhs, op, rhs) => {
+                let lhs = lhs.eval(debugger);
+                let rhs = rhs.eval(debugger);
+                match op.as_str() {
+                    "+" => lhs.wrapping_add(rhs),
+                    "-" => lhs.wrapping_sub(rhs),
+                    "*" => lhs.wrapping_mul(rhs),
+                    "==" => (lhs == rhs) as Word,
+                    "!=" => (lhs != rhs) as Word,
+                    "<" => (lhs < rhs) as Word,
+                    "<=" => (lhs <= rhs) as Word,
+                    ">" => (lhs > rhs) as Word,
+                    _ => (lhs >= rhs) as Word,
+                }
+            }
+        };
+    }
+}
+
+impl std::fmt::Display for Expr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Expr::Number(value) => write!(f, "{}", value),
+            Expr::Register(register) => write!(f, "{}", REGISTERS[*register]),
+            Expr::Ip => write!(f, "ip"),
+            Expr::Steps => write!(f, "steps"),
+            Expr::Binary(lhs, op, rhs) => write!(f, "({} {} {})", lhs, op, rhs),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day8::{Day8, EXAMPLE};
+    use crate::solver::Solver;
+
+    fn debugger() -> Debugger {
+        return Debugger::new(Day8::parse(EXAMPLE).unwrap(), 100);
+    }
+
+    fn run(debugger: &mut Debugger, command: &str) -> String {
+        return debugger.execute(command).unwrap().unwrap();
+    }
+
+    #[test]
+    fn run_until_loop() {
+        let mut debugger = debugger();
+        let output = run(&mut debugger, "run");
+        assert!(output.starts_with("infinite loop: instruction 1"));
+        assert_eq!(debugger.computer.accumulator(), 5);
+        assert!(run(&mut debugger, "visited").starts_with("7 of 9 instructions visited: 0 1 2 3 4 6 7"));
+    }
+
+    #[test]
+    fn breakpoints() {
+        let mut debugger = debugger();
+        run(&mut debugger, "break 4");
+        assert!(run(&mut debugger, "run").starts_with("breakpoint 0 hit"));
+        assert_eq!(debugger.computer.ip, 4);
+
+        run(&mut debugger, "delete 0");
+        run(&mut debugger, "reset");
+        run(&mut debugger, "break acc 5");
+        assert!(run(&mut debugger, "run").starts_with("breakpoint 0 hit"));
+        assert_eq!(debugger.steps, 6);
+    }
+
+    #[test]
+    fn step_and_reset() {
+        let mut debugger = debugger();
+        run(&mut debugger, "step 3");
+        assert_eq!((debugger.computer.ip, debugger.computer.accumulator()), (6, 1));
+        run(&mut debugger, "reset");
+        assert_eq!((debugger.computer.ip, debugger.steps), (0, 0));
+        assert!(debugger.visited.is_empty());
+    }
+
+    #[test]
+    fn watch_expressions() {
+        let mut debugger = debugger();
+        assert_eq!(run(&mut debugger, "watch acc * 2 + 1"), "watch 0: ((acc * 2) + 1) = 1");
+        run(&mut debugger, "watch ip == 2");
+        let output = run(&mut debugger, "step 2");
+        assert!(output.contains("watch 0: ((acc * 2) + 1) = 3"));
+        assert!(output.contains("watch 1: (ip == 2) = 1"));
+        assert!(debugger.execute("watch acc +").unwrap().is_err());
+    }
+
+    #[test]
+    fn repl() {
+        let mut debugger = debugger();
+        let mut output = Vec::new();
+        debugger.repl("step\nfoo\nquit\nstep\n".as_bytes(), &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("error: unknown command 'foo'"));
+        assert_eq!(debugger.steps, 1);
+    }
+
+    #[test]
+    fn registers_and_io() {
+        let mut debugger = Debugger::new(Day8::parse("in r1\nmul r1 +3\nout r1").unwrap(), 100);
+        run(&mut debugger, "input 7");
+        run(&mut debugger, "watch r1 + 1");
+        assert!(run(&mut debugger, "step 2").contains("watch 0: (r1 + 1) = 22"));
+        run(&mut debugger, "run");
+        assert_eq!(run(&mut debugger, "output"), "21");
+    }
+
+    #[test]
+    fn reverse_stepping() {
+        let mut debugger = debugger();
+        run(&mut debugger, "run");
+        assert!(run(&mut debugger, "back 3").starts_with("ip = 7  acc = 2  r1 = 0  r2 = 0  r3 = 0  steps = 4"));
+        assert!(run(&mut debugger, "visited").starts_with("4 of 9 instructions visited: 0 1 2 6"));
+        assert_eq!(run(&mut debugger, "trace 2"), "     2     2  jmp +4       acc 1 -> 1\n     3     6  acc +1       acc 1 -> 2");
+
+        assert!(run(&mut debugger, "back 10").starts_with("no earlier steps recorded, went back 4 step(s)"));
+        assert_eq!((debugger.computer.ip, debugger.steps), (0, 0));
+
+        // only the last 2 steps are kept
+        let mut debugger = Debugger::new(Day8::parse(EXAMPLE).unwrap(), 2);
+        run(&mut debugger, "step 5");
+        assert!(run(&mut debugger, "back 3").starts_with("no earlier steps recorded, went back 2 step(s)"));
+        assert_eq!(debugger.steps, 3);
+    }
+}
@@ -0,0 +1,243 @@
+use super::{Computer, Instruction};
+use super::cfg::{self, Cfg, Flow};
+use std::collections::BTreeSet;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GraphFormat {
+    Dot,
+    Mermaid,
+}
+
+impl FromStr for GraphFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<GraphFormat, String> {
+        match s {
+            "dot" => Ok(GraphFormat::Dot),
+            "mermaid" => Ok(GraphFormat::Mermaid),
+            _ => Err(format!("unknown graph format '{}', expected dot or mermaid", s)),
+        }
+    }
+}
+
+// What a node of the exported graph is highlighted as
+struct Highlights {
+    executed: BTreeSet<usize>,
+    // instructions of the infinite loop, in execution order
+    cycle: Vec<usize>,
+    // instruction the program stops on with an error, e.g. a jump out of bounds
+    error: Option<usize>,
+    // executed jmp/nop instructions, flipping any other one can't change the path
+    candidates: BTreeSet<usize>,
+    repair: Option<usize>,
+}
+
+impl Highlights {
+    fn analyse(program: &[Instruction]) -> Highlights {
+        let (path, _) = cfg::executed(program);
+
+        let mut computer = Computer::load_program(program.to_vec());
+        let (cycle, error) = match computer.run() {
+            None => match computer.current() {
+                Some(start) => (path.iter().skip_while(|ip| **ip != start).copied().collect(), None),
+                None => (Vec::new(), None),
+            },
+            // a failing instruction leaves the computer at it
+            Some(Err(_)) => (Vec::new(), computer.current()),
+            Some(Ok(_)) => (Vec::new(), None),
+        };
+
+        return Highlights {
+            candidates: path.iter().copied().filter(|ip| program[*ip].flipped().is_some()).collect(),
+            executed: path.into_iter().collect(),
+            cycle: cycle,
+            error: error,
+            repair: cfg::find_repair(program).map(|repair| repair.index),
+        };
+    }
+
+    fn is_cycle_edge(&self, from: usize, to: usize) -> bool {
+        let position = match self.cycle.iter().position(|ip| *ip == from) {
+            Some(position) => position,
+            None => return false,
+        };
+        return self.cycle[(position + 1) % self.cycle.len()] == to;
+    }
+}
+
+fn node(index: usize, exit: usize) -> String {
+    if index == exit {
+        return String::from("exit");
+    }
+    return format!("n{}", index);
+}
+
+// all edges of the graph as (from, to, flow)
+fn edges(cfg: &Cfg) -> Vec<(usize, usize, Flow)> {
+    let mut edges = Vec::new();
+    for from in 0..cfg.exit() {
+        for (to, flow) in cfg.successors(from) {
+            edges.push((from, *to, *flow));
+        }
+    }
+    return edges;
+}
+
+// Renders the control flow graph of a program. Executed instructions are
+// filled, the infinite loop is drawn in red and an instruction the program
+// fails on in yellow, candidate repair sites get a thick border and the
+// actual repair a green one.
+pub fn render(program: &[Instruction], format: GraphFormat) -> String {
+    let cfg = Cfg::build(program);
+    let highlights = Highlights::analyse(program);
+    match format {
+        GraphFormat::Dot => render_dot(program, &cfg, &highlights),
+        GraphFormat::Mermaid => render_mermaid(program, &cfg, &highlights),
+    }
+}
+
+fn render_dot(program: &[Instruction], cfg: &Cfg, highlights: &Highlights) -> String {
+    let exit = cfg.exit();
+    let mut lines = vec![
+        String::from("digraph program {"),
+        String::from("    node [shape=box, fontname=\"monospace\"];"),
+    ];
+
+    for (index, instruction) in program.iter().enumerate() {
+        let mut attributes = vec![format!("label=\"{}: {}\"", index, instruction)];
+        if highlights.cycle.contains(&index) {
+            attributes.push(String::from("style=filled, fillcolor=\"#ffb3b3\""));
+        } else if highlights.error == Some(index) {
+            attributes.push(String::from("style=filled, fillcolor=\"#ffe080\""));
+        } else if highlights.executed.contains(&index) {
+            attributes.push(String::from("style=filled, fillcolor=\"#dddddd\""));
+        }
+        if highlights.repair == Some(index) {
+            attributes.push(String::from("color=\"#00a000\", penwidth=3"));
+        } else if highlights.candidates.contains(&index) {
+            attributes.push(String::from("color=\"#ff8000\", penwidth=3"));
+        }
+        lines.push(format!("    {} [{}];", node(index, exit), attributes.join(", ")));
+    }
+    lines.push(String::from("    exit [label=\"end\", shape=doublecircle];"));
+
+    for (from, to, flow) in edges(cfg) {
+        let mut attributes = Vec::new();
+        if flow == Flow::Jump {
+            attributes.push(String::from("style=dashed"));
+        }
+        if highlights.is_cycle_edge(from, to) {
+            attributes.push(String::from("color=red, penwidth=2"));
+        }
+        match attributes.is_empty() {
+            true => lines.push(format!("    {} -> {};", node(from, exit), node(to, exit))),
+            false => lines.push(format!("    {} -> {} [{}];", node(from, exit), node(to, exit), attributes.join(", "))),
+        }
+    }
+    lines.push(String::from("}"));
+    return lines.join("\n") + "\n";
+}
+
+fn class_line(class: &str, nodes: &[String]) -> Option<String> {
+    if nodes.is_empty() {
+        return None;
+    }
+    return Some(format!("    class {} {}", nodes.join(","), class));
+}
+
+fn render_mermaid(program: &[Instruction], cfg: &Cfg, highlights: &Highlights) -> String {
+    let exit = cfg.exit();
+    let mut lines = vec![String::from("flowchart TD")];
+
+    for (index, instruction) in program.iter().enumerate() {
+        lines.push(format!("    {}[\"{}: {}\"]", node(index, exit), index, instruction));
+    }
+    lines.push(String::from("    exit((end))"));
+
+    let mut cycle_edges = Vec::new();
+    for (number, (from, to, flow)) in edges(cfg).into_iter().enumerate() {
+        let arrow = match flow {
+            Flow::FallThrough => "-->",
+            Flow::Jump => "-.->",
+        };
+        lines.push(format!("    {} {} {}", node(from, exit), arrow, node(to, exit)));
+        if highlights.is_cycle_edge(from, to) {
+            cycle_edges.push(number.to_string());
+        }
+    }
+
+    lines.push(String::from("    classDef executed fill:#dddddd"));
+    lines.push(String::from("    classDef cycle fill:#ffb3b3"));
+    lines.push(String::from("    classDef error fill:#ffe080"));
+    lines.push(String::from("    classDef candidate stroke:#ff8000,stroke-width:3px"));
+    lines.push(String::from("    classDef repair stroke:#00a000,stroke-width:3px"));
+
+    let nodes = |filter: &dyn Fn(usize) -> bool| -> Vec<String> {
+        return (0..program.len()).filter(|index| filter(*index)).map(|index| node(index, exit)).collect();
+    };
+    let executed = nodes(&|index| {
+        highlights.executed.contains(&index) && !highlights.cycle.contains(&index) && highlights.error != Some(index)
+    });
+    let cycle = nodes(&|index| highlights.cycle.contains(&index));
+    let error = nodes(&|index| highlights.error == Some(index));
+    let candidates = nodes(&|index| highlights.candidates.contains(&index) && highlights.repair != Some(index));
+    let repair = nodes(&|index| highlights.repair == Some(index));
+    lines.extend(class_line("executed", &executed));
+    lines.extend(class_line("cycle", &cycle));
+    lines.extend(class_line("error", &error));
+    lines.extend(class_line("candidate", &candidates));
+    lines.extend(class_line("repair", &repair));
+    if !cycle_edges.is_empty() {
+        lines.push(format!("    linkStyle {} stroke:red,stroke-width:2px", cycle_edges.join(",")));
+    }
+    return lines.join("\n") + "\n";
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day8::{Day8, EXAMPLE};
+    use crate::solver::Solver;
+
+    #[test]
+    fn dot() {
+        let program = Day8::parse(EXAMPLE).unwrap();
+        let dot = render(&program, GraphFormat::Dot);
+        assert!(dot.starts_with("digraph program {\n"));
+        assert!(dot.contains("    n0 [label=\"0: nop +0\", style=filled, fillcolor=\"#dddddd\", color=\"#ff8000\", penwidth=3];"));
+        assert!(dot.contains("    n7 [label=\"7: jmp -4\", style=filled, fillcolor=\"#ffb3b3\", color=\"#00a000\", penwidth=3];"));
+        assert!(dot.contains("    n5 [label=\"5: acc -99\"];"));
+        assert!(dot.contains("    n4 -> n1 [style=dashed, color=red, penwidth=2];"));
+        assert!(dot.contains("    n8 -> exit;"));
+        assert!(dot.contains("    n0 -> n1;"));
+    }
+
+    #[test]
+    fn mermaid() {
+        let program = Day8::parse(EXAMPLE).unwrap();
+        let mermaid = render(&program, GraphFormat::Mermaid);
+        assert!(mermaid.contains("    n2[\"2: jmp +4\"]"));
+        assert!(mermaid.contains("    n2 -.-> n6"));
+        assert!(mermaid.contains("    class n0 executed"));
+        assert!(mermaid.contains("    class n1,n2,n3,n4,n6,n7 cycle"));
+        assert!(mermaid.contains("    class n0,n2,n4 candidate"));
+        assert!(mermaid.contains("    class n7 repair"));
+        assert!(mermaid.contains("    linkStyle 1,2,3,4,6,7 stroke:red,stroke-width:2px"));
+        assert!(!mermaid.contains("    class n1 error"));
+    }
+
+    #[test]
+    fn error_instead_of_loop() {
+        // the jump leaves the program, nothing runs twice, flipping it repairs the program
+        let program = Day8::parse("acc +1\njmp -5").unwrap();
+        let dot = render(&program, GraphFormat::Dot);
+        assert!(dot.contains("    n0 [label=\"0: acc +1\", style=filled, fillcolor=\"#dddddd\"];"));
+        assert!(dot.contains("    n1 [label=\"1: jmp -5\", style=filled, fillcolor=\"#ffe080\", color=\"#00a000\", penwidth=3];"));
+        assert!(!dot.contains("#ffb3b3") && !dot.contains("color=red"));
+
+        let mermaid = render(&program, GraphFormat::Mermaid);
+        assert!(mermaid.contains("    class n0 executed\n    class n1 error\n"));
+        assert!(!mermaid.contains(" cycle\n") && !mermaid.contains("linkStyle"));
+    }
+}
@@ -0,0 +1,137 @@
+use super::Computer;
+use super::vm::{VmError, Word};
+use crate::input::ParseError;
+use std::fmt;
+use std::str::FromStr;
+
+// Register names, `acc` is the accumulator of the original puzzle
+pub const REGISTERS: [&str; 4] = ["acc", "r1", "r2", "r3"];
+
+// What an operand of an opcode accepts
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OperandKind {
+    Register,
+    // a number or a register
+    Value,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Operand {
+    Number(Word),
+    Register(usize),
+}
+
+impl Operand {
+    pub fn parse(text: &str, kind: OperandKind) -> Result<Operand, ParseError> {
+        if let Some(register) = REGISTERS.iter().position(|name| *name == text) {
+            return Ok(Operand::Register(register));
+        }
+        if kind == OperandKind::Register {
+            return Err(ParseError::new(format!("expected a register, found '{}'", text)));
+        }
+        return Ok(Operand::Number(Word::from_str(text)?));
+    }
+}
+
+// numbers keep the sign of the puzzle format, e.g. `+3`
+impl fmt::Display for Operand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Operand::Number(value) => write!(f, "{:+}", value),
+            Operand::Register(register) => write!(f, "{}", REGISTERS[*register]),
+        }
+    }
+}
+
+// An entry of the instruction table. The number of operands is the arity of
+// the opcode, `execute` applies the instruction to the computer and returns
+// the offset to the next instruction. Failing instructions must not change
+// the computer.
+#[derive(Debug)]
+pub struct Opcode {
+    pub mnemonic: &'static str,
+    pub operands: &'static [OperandKind],
+    // operand holding the relative jump offset, for instructions that jump
+    // (`nop` counts as well, since it becomes a `jmp` when repaired)
+    pub target: Option<usize>,
+    pub(super) execute: fn(&mut Computer, &[Operand]) -> Result<Word, VmError>,
+}
+
+const VALUE: &[OperandKind] = &[OperandKind::Value];
+const REGISTER: &[OperandKind] = &[OperandKind::Register];
+const REGISTER_VALUE: &[OperandKind] = &[OperandKind::Register, OperandKind::Value];
+const VALUE_VALUE: &[OperandKind] = &[OperandKind::Value, OperandKind::Value];
+
+fn set(computer: &mut Computer, register: &Operand, value: Word) {
+    if let Operand::Register(register) = register {
+        computer.registers[*register] = value;
+    }
+}
+
+// stores the result of a checked operation on two operands in the first one
+fn arithmetic(computer: &mut Computer, args: &[Operand], op: fn(Word, Word) -> Option<Word>) -> Result<Word, VmError> {
+    let value = computer.checked(op(computer.value(&args[0])?, computer.value(&args[1])?))?;
+    set(computer, &args[0], value);
+    return Ok(1);
+}
+
+fn jump_if(computer: &Computer, args: &[Operand], condition: fn(Word) -> bool) -> Result<Word, VmError> {
+    if condition(computer.value(&args[0])?) {
+        return computer.value(&args[1]);
+    }
+    return Ok(1);
+}
+
+pub const OPCODES: &[Opcode] = &[
+    // instructions of the puzzle
+    Opcode { mnemonic: "acc", operands: VALUE, target: None, execute: |c, args| {
+        c.registers[0] = c.checked(c.registers[0].checked_add(c.value(&args[0])?))?;
+        Ok(1)
+    }},
+    Opcode { mnemonic: "jmp", operands: VALUE, target: Some(0), execute: |c, args| c.value(&args[0]) },
+    Opcode { mnemonic: "nop", operands: VALUE, target: Some(0), execute: |_, _| Ok(1) },
+
+    // registers
+    Opcode { mnemonic: "mov", operands: REGISTER_VALUE, target: None, execute: |c, args| {
+        let value = c.value(&args[1])?;
+        set(c, &args[0], value);
+        Ok(1)
+    }},
+    Opcode { mnemonic: "add", operands: REGISTER_VALUE, target: None, execute: |c, args| {
+        arithmetic(c, args, Word::checked_add)
+    }},
+    Opcode { mnemonic: "sub", operands: REGISTER_VALUE, target: None, execute: |c, args| {
+        arithmetic(c, args, Word::checked_sub)
+    }},
+    Opcode { mnemonic: "mul", operands: REGISTER_VALUE, target: None, execute: |c, args| {
+        arithmetic(c, args, Word::checked_mul)
+    }},
+
+    // conditional jumps, `jz r1 +3` jumps 3 instructions ahead if r1 is zero
+    Opcode { mnemonic: "jz", operands: VALUE_VALUE, target: Some(1), execute: |c, args| {
+        jump_if(c, args, |v| v == 0)
+    }},
+    Opcode { mnemonic: "jnz", operands: VALUE_VALUE, target: Some(1), execute: |c, args| {
+        jump_if(c, args, |v| v != 0)
+    }},
+    Opcode { mnemonic: "jgz", operands: VALUE_VALUE, target: Some(1), execute: |c, args| {
+        jump_if(c, args, |v| v > 0)
+    }},
+
+    // I/O, `in` reads 0 once the input is exhausted
+    Opcode { mnemonic: "in", operands: REGISTER, target: None, execute: |c, args| {
+        let value = c.checked(Some(c.input.front().copied().unwrap_or(0)))?;
+        c.input.pop_front();
+        set(c, &args[0], value);
+        Ok(1)
+    }},
+    Opcode { mnemonic: "out", operands: VALUE, target: None, execute: |c, args| {
+        let value = c.value(&args[0])?;
+        c.output.push(value);
+        Ok(1)
+    }},
+];
+
+pub fn lookup(mnemonic: &str) -> Option<&'static Opcode> {
+    return OPCODES.iter().find(|opcode| opcode.mnemonic == mnemonic);
+}
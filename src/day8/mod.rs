@@ -0,0 +1,510 @@
+use std::collections::VecDeque;
+use std::fmt;
+use crate::input::{self, InputError, ParseError, Source};
+use crate::output::{self, Format};
+use crate::solver::Solver;
+
+mod analysis;
+mod asm;
+mod cfg;
+mod debugger;
+mod graph;
+mod isa;
+mod trace;
+mod vm;
+
+use isa::{Opcode, Operand, REGISTERS};
+use trace::Tracer;
+use vm::{StepResult, VmError, Width, Word};
+
+// wide enough for the puzzle input and most generated programs
+const DEFAULT_WIDTH: Width = Width::I64;
+
+// An opcode of the instruction table together with its operands
+#[derive(Clone, Debug)]
+pub struct Instruction {
+    opcode: &'static Opcode,
+    operands: Vec<Operand>,
+}
+
+impl Instruction {
+    pub fn new(opcode: &'static Opcode, operands: Vec<Operand>) -> Instruction {
+        return Instruction {
+            opcode: opcode,
+            operands: operands,
+        };
+    }
+
+    pub fn opcode(&self) -> &'static Opcode {
+        return self.opcode;
+    }
+
+    pub fn operands(&self) -> &[Operand] {
+        return &self.operands;
+    }
+
+    // relative jump offset, if the instruction jumps by a fixed number of instructions
+    pub fn offset(&self) -> Option<Word> {
+        match self.operands.get(self.opcode.target?) {
+            Some(Operand::Number(offset)) => Some(*offset),
+            _ => None,
+        }
+    }
+
+    // Index a jump by the fixed offset leads to from `index`. None if the
+    // instruction has no fixed offset or the jump leaves a program of `len`
+    // instructions other than at its end.
+    pub fn jump_target(&self, index: usize, len: usize) -> Option<usize> {
+        let target = (index as Word).checked_add(self.offset()?)?;
+        if target < 0 || target > len as Word {
+            return None;
+        }
+        return Some(target as usize);
+    }
+
+    // the instruction with jmp and nop swapped, None for every other instruction
+    pub fn flipped(&self) -> Option<Instruction> {
+        let mnemonic = match self.opcode.mnemonic {
+            "jmp" => "nop",
+            "nop" => "jmp",
+            _ => return None,
+        };
+        return Some(Instruction::new(isa::lookup(mnemonic)?, self.operands.clone()));
+    }
+
+    // the instruction patched: jmp and nop swapped, or acc turned into nop
+    pub fn patched(&self) -> Option<Instruction> {
+        if self.opcode.mnemonic == "acc" {
+            return Some(Instruction::new(isa::lookup("nop")?, self.operands.clone()));
+        }
+        return self.flipped();
+    }
+}
+
+impl PartialEq for Instruction {
+    fn eq(&self, other: &Instruction) -> bool {
+        return self.opcode.mnemonic == other.opcode.mnemonic && self.operands == other.operands;
+    }
+}
+
+// same format as the puzzle input, e.g. `acc +1`
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.opcode.mnemonic)?;
+        for operand in &self.operands {
+            write!(f, " {}", operand)?;
+        }
+        return Ok(());
+    }
+}
+
+struct Computer {
+    program: Vec<Instruction>,
+    width: Width,
+    // register 0 is the accumulator
+    registers: [Word; REGISTERS.len()],
+    ip: usize,
+    input: VecDeque<Word>,
+    output: Vec<Word>,
+    // records the executed steps, only if tracing is enabled
+    tracer: Option<Tracer>,
+}
+
+impl Computer {
+    fn parse_instruction(line: &str) -> Result<Instruction, ParseError> {
+        let mut parts = line.split_whitespace();
+        let mnemonic = parts.next()
+            .ok_or_else(|| ParseError::new(String::from("expected '<instruction> <operands>'")))?;
+        let opcode = isa::lookup(mnemonic)
+            .ok_or_else(|| ParseError::new(format!("unknown instruction '{}'", mnemonic)))?;
+
+        let operands: Vec<&str> = parts.collect();
+        if operands.len() != opcode.operands.len() {
+            let message = format!("{} expects {} operand(s), found {}", mnemonic, opcode.operands.len(), operands.len());
+            return Err(ParseError::new(message));
+        }
+        let operands = operands.iter()
+            .zip(opcode.operands)
+            .map(|(text, kind)| Operand::parse(text, *kind))
+            .collect::<Result<Vec<Operand>, ParseError>>()?;
+        return Ok(Instruction::new(opcode, operands));
+    }
+
+    fn load_program(program: Vec<Instruction>) -> Computer {
+        return Computer {
+            program: program,
+            width: DEFAULT_WIDTH,
+            registers: [0; REGISTERS.len()],
+            ip: 0,
+            input: VecDeque::new(),
+            output: Vec::new(),
+            tracer: None,
+        }
+    }
+
+    fn with_width(mut self, width: Width) -> Computer {
+        self.width = width;
+        return self;
+    }
+
+    // keeps the last `capacity` steps from now on
+    fn trace(&mut self, capacity: usize) {
+        self.tracer = Some(Tracer::new(capacity));
+    }
+
+    fn accumulator(&self) -> Word {
+        return self.registers[0];
+    }
+
+    // result of an arithmetic operation, if it fits the register width
+    fn checked(&self, value: Option<Word>) -> Result<Word, VmError> {
+        match value {
+            Some(value) if self.width.contains(value) => Ok(value),
+            _ => Err(VmError::Overflow { ip: self.ip }),
+        }
+    }
+
+    fn value(&self, operand: &Operand) -> Result<Word, VmError> {
+        match operand {
+            Operand::Number(value) => self.checked(Some(*value)),
+            Operand::Register(register) => Ok(self.registers[*register]),
+        }
+    }
+
+    // index of the next instruction, None once the program left the instruction list
+    fn current(&self) -> Option<usize> {
+        if self.ip < self.program.len() {
+            return Some(self.ip);
+        }
+        return None;
+    }
+
+    fn is_terminated(&self) -> bool {
+        return self.ip == self.program.len();
+    }
+
+    fn next(&mut self) -> StepResult {
+        let ip = match self.current() {
+            Some(ip) => ip,
+            None => return StepResult::Halted,
+        };
+        let instruction = &self.program[ip];
+        let (execute, operands) = (instruction.opcode.execute, instruction.operands.clone());
+        let (before, front, input, output) = (self.registers, self.input.front().copied(), self.input.len(), self.output.len());
+
+        let offset = match execute(self, &operands) {
+            Ok(offset) => offset,
+            Err(error) => return StepResult::Error(error),
+        };
+        let target = (ip as Word).checked_add(offset);
+        let next_ip = match target {
+            Some(target) if (0..=self.program.len() as Word).contains(&target) => target as usize,
+            _ => {
+                // leave the computer unchanged, like a failing instruction
+                self.registers = before;
+                self.output.truncate(output);
+                if self.input.len() < input {
+                    self.input.push_front(front.unwrap_or(0));
+                }
+                let error = match target {
+                    Some(target) => VmError::JumpOutOfBounds { ip: ip, target: target },
+                    None => VmError::JumpOverflow { ip: ip, offset: offset },
+                };
+                return StepResult::Error(error);
+            }
+        };
+        self.ip = next_ip;
+
+        if let Some(tracer) = self.tracer.as_mut() {
+            tracer.record(trace::Step {
+                number: tracer.next_number(),
+                ip: ip,
+                next_ip: next_ip,
+                instruction: self.program[ip].clone(),
+                before: before,
+                after: self.registers,
+                input: if self.input.len() < input { front } else { None },
+                output: self.output.get(output).copied(),
+            });
+        }
+
+        if self.is_terminated() {
+            return StepResult::Halted;
+        }
+        return StepResult::Continue;
+    }
+
+    // Undoes the most recent traced step, returns false if there is none left
+    fn step_back(&mut self) -> bool {
+        let step = match self.tracer.as_mut().and_then(|tracer| tracer.pop()) {
+            Some(step) => step,
+            None => return false,
+        };
+        self.ip = step.ip;
+        self.registers = step.before;
+        if let Some(value) = step.input {
+            self.input.push_front(value);
+        }
+        if step.output.is_some() {
+            self.output.pop();
+        }
+        return true;
+    }
+
+    // Runs until an instruction is about to be executed a second time. This is
+    // exact for the puzzle instructions, with conditional jumps a revisited
+    // instruction does not necessarily mean the program loops forever. A
+    // program stopped by an error doesn't terminate either.
+    fn has_infinite_loop(&mut self) -> bool {
+        let mut visited = vec![false; self.program.len()];
+
+        while let Some(ip) = self.current() {
+            if visited[ip] {
+                return true;
+            }
+            visited[ip] = true;
+            if let StepResult::Error(_) = self.next() {
+                return true;
+            }
+        }
+        return false;
+    }
+
+    // Runs until the program halts or fails, None if it loops forever
+    fn run(&mut self) -> Option<Result<Word, VmError>> {
+        let mut visited = vec![false; self.program.len()];
+        loop {
+            if let Some(ip) = self.current() {
+                if visited[ip] {
+                    return None;
+                }
+                visited[ip] = true;
+            }
+            match self.next() {
+                StepResult::Continue => (),
+                StepResult::Halted => return Some(Ok(self.accumulator())),
+                StepResult::Error(error) => return Some(Err(error)),
+            }
+        }
+    }
+}
+
+fn load(source: &Source) -> Result<Vec<Instruction>, String> {
+    let input = source.read().map_err(|e| e.to_string())?;
+    return Day8::parse(&input).map_err(|e| e.in_file(source.name()).to_string());
+}
+
+const USAGE: &str = "usage: aoc 8 debug [trace size] | aoc 8 run [width] [--in <n,n,...>] | aoc 8 trace | aoc 8 disasm \
+| aoc 8 cfg [dot|mermaid] | aoc 8 analyse | aoc 8 repair | aoc 8 asm <file>|-";
+
+// Subcommands of `aoc 8`:
+//   debug [size] interactive debugger for the puzzle input, keeping the last
+//                `size` steps for stepping backwards
+//   run [width] [--in <values>]
+//                run the puzzle input with i16, i32, i64 (default) or i128
+//                registers, `in` reads the comma separated values and the
+//                values written by `out` are printed
+//   trace        steps executed until the infinite loop, as JSON lines
+//   disasm       print the puzzle input as label based source
+//   cfg [format] control flow graph of the puzzle input as dot (default) or mermaid
+//   analyse      classify the instructions and list every repairing patch
+//   repair       find the jmp/nop flip that makes the puzzle input terminate
+//   asm <file>   assemble label based source into the puzzle format
+pub fn tool(name: &str, args: &[String], source: &Source, format: Format) -> Result<(), String> {
+    output::text_only(&format!("aoc 8 {}", name), format)?;
+    match (name, args) {
+        ("debug", []) => {
+            let mut debugger = debugger::Debugger::new(load(source)?, trace::DEFAULT_TRACE_SIZE);
+            let stdin = std::io::stdin();
+            return debugger.repl(stdin.lock(), &mut std::io::stdout()).map_err(|e| e.to_string());
+        }
+        ("debug", [size]) => {
+            let size = size.parse::<usize>().map_err(|_| format!("invalid trace size '{}'", size))?;
+            let mut debugger = debugger::Debugger::new(load(source)?, size);
+            let stdin = std::io::stdin();
+            return debugger.repl(stdin.lock(), &mut std::io::stdout()).map_err(|e| e.to_string());
+        }
+        ("disasm", []) => {
+            print!("{}", asm::disassemble(&load(source)?));
+            return Ok(());
+        }
+        ("cfg", []) => {
+            print!("{}", graph::render(&load(source)?, graph::GraphFormat::Dot));
+            return Ok(());
+        }
+        ("cfg", [format]) => {
+            print!("{}", graph::render(&load(source)?, format.parse::<graph::GraphFormat>()?));
+            return Ok(());
+        }
+        ("run", args) => {
+            let mut width = None;
+            let mut input = VecDeque::new();
+            let mut args = args.iter();
+            while let Some(arg) = args.next() {
+                match arg.as_str() {
+                    "--in" => {
+                        let values = args.next().ok_or(format!("missing values for {}", arg))?;
+                        for value in values.split(',') {
+                            input.push_back(value.trim().parse::<Word>().map_err(|_| format!("invalid input value '{}'", value))?);
+                        }
+                    }
+                    arg if width.is_none() => width = Some(arg.parse::<Width>()?),
+                    _ => return Err(String::from(USAGE)),
+                }
+            }
+            let mut computer = Computer::load_program(load(source)?).with_width(width.unwrap_or(DEFAULT_WIDTH));
+            computer.input = input;
+            let result = computer.run();
+            if !computer.output.is_empty() {
+                let output: Vec<String> = computer.output.iter().map(|v| v.to_string()).collect();
+                println!("output: {}", output.join(" "));
+            }
+            match result {
+                Some(Ok(accumulator)) => println!("program terminated, accumulator {}", accumulator),
+                Some(Err(error)) => return Err(error.to_string()),
+                None => println!("infinite loop at instruction {}, accumulator {}", computer.ip, computer.accumulator()),
+            }
+            return Ok(());
+        }
+        ("trace", []) => {
+            let mut computer = Computer::load_program(load(source)?);
+            computer.trace(trace::DEFAULT_TRACE_SIZE);
+            computer.has_infinite_loop();
+            print!("{}", computer.tracer.map(|tracer| tracer.to_jsonl()).unwrap_or_default());
+            return Ok(());
+        }
+        ("analyse", []) => {
+            print!("{}", analysis::analyse(&load(source)?));
+            return Ok(());
+        }
+        ("repair", []) => {
+            match cfg::find_repair(&load(source)?) {
+                Some(repair) => println!("flip instruction {}: '{}' -> '{}', accumulator {}",
+                    repair.index, repair.old, repair.new, repair.accumulator),
+                None => println!("no single jmp/nop flip makes the program terminate"),
+            }
+            return Ok(());
+        }
+        ("asm", [file]) => {
+            let source = Source::from_arg(file);
+            let text = source.read().map_err(|e| e.to_string())?;
+            let program = asm::assemble(&text).map_err(|e| e.in_file(source.name()).to_string())?;
+            print!("{}", asm::to_text(&program));
+            return Ok(());
+        }
+        _ => Err(String::from(USAGE)),
+    }
+}
+
+pub struct Day8;
+
+impl Solver for Day8 {
+    type Input = Vec<Instruction>;
+    type Answer1 = Word;
+    type Answer2 = Word;
+
+    fn parse(input: &str) -> Result<Vec<Instruction>, InputError> {
+        return input::lines_with(input, Computer::parse_instruction);
+    }
+
+    fn part1(program: &Vec<Instruction>) -> Option<Word> {
+        let mut computer = Computer::load_program(program.clone());
+        computer.has_infinite_loop();
+        return Some(computer.accumulator());
+    }
+
+    fn part2(program: &Vec<Instruction>) -> Option<Word> {
+        return cfg::find_repair(program).map(|repair| repair.accumulator);
+    }
+}
+
+// example program of the puzzle, used by the tests of all submodules
+#[cfg(test)]
+const EXAMPLE: &str = "\
+nop +0
+acc +1
+jmp +4
+acc +3
+jmp -3
+acc -99
+acc +1
+jmp -4
+acc +6";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn example() {
+        let input = Day8::parse(EXAMPLE).unwrap();
+        assert_eq!(Day8::part1(&input), Some(5));
+        assert_eq!(Day8::part2(&input), Some(8));
+    }
+
+    #[test]
+    fn instruction_table() {
+        // sums up 1..=n for every value of the input
+        let program = Day8::parse("\
+in r1
+jz r1 +7
+mov acc +0
+add acc r1
+sub r1 +1
+jgz r1 -2
+out acc
+jmp -7
+nop +0").unwrap();
+        let mut computer = Computer::load_program(program);
+        computer.input.extend(vec![4, 10]);
+        while computer.current().is_some() {
+            computer.next();
+        }
+        assert!(computer.is_terminated());
+        assert_eq!(computer.output, vec![10, 55]);
+
+        assert_eq!(Day8::parse("acc +1\nmul acc").unwrap_err().to_string(),
+            "<input>:2: mul expects 2 operand(s), found 1 (in 'mul acc')");
+        assert_eq!(Day8::parse("hlt +0").unwrap_err().to_string(),
+            "<input>:1: unknown instruction 'hlt' (in 'hlt +0')");
+        assert!(Day8::parse("mov +1 +2").is_err());
+    }
+
+    #[test]
+    fn checked_arithmetic() {
+        let program = Day8::parse("acc +30000\nacc +30000\nnop +0").unwrap();
+        let mut computer = Computer::load_program(program.clone()).with_width(Width::I16);
+        assert_eq!(computer.next(), StepResult::Continue);
+        assert_eq!(computer.next(), StepResult::Error(VmError::Overflow { ip: 1 }));
+        assert_eq!((computer.ip, computer.accumulator()), (1, 30000));
+
+        let mut computer = Computer::load_program(program).with_width(Width::I32);
+        assert_eq!(computer.run(), Some(Ok(60000)));
+        assert_eq!(computer.next(), StepResult::Halted);
+
+        let program = Day8::parse("mov r1 +9223372036854775807\nadd r1 +1").unwrap();
+        assert_eq!(Computer::load_program(program.clone()).run(), Some(Err(VmError::Overflow { ip: 1 })));
+        assert_eq!(Computer::load_program(program).with_width(Width::I128).run(), Some(Ok(0)));
+    }
+
+    #[test]
+    fn jumps_out_of_bounds() {
+        let program = Day8::parse("acc +1\njmp -5").unwrap();
+        let mut computer = Computer::load_program(program);
+        assert_eq!(computer.run(), Some(Err(VmError::JumpOutOfBounds { ip: 1, target: -4 })));
+        assert_eq!(computer.ip, 1);
+        assert!(computer.has_infinite_loop());
+
+        let mut computer = Computer::load_program(Day8::parse("out +1\njmp +3").unwrap());
+        computer.next();
+        assert!(matches!(computer.next(), StepResult::Error(VmError::JumpOutOfBounds { ip: 1, target: 4 })));
+        assert_eq!(computer.output, vec![1]);
+
+        // the target itself doesn't fit into a word
+        let program = Day8::parse("nop +0\njmp +170141183460469231731687303715884105727").unwrap();
+        let mut computer = Computer::load_program(program).with_width(Width::I128);
+        let error = VmError::JumpOverflow { ip: 1, offset: Word::MAX };
+        assert_eq!(computer.run(), Some(Err(error.clone())));
+        assert_eq!(error.to_string(), "instruction 1 jumps by 170141183460469231731687303715884105727, past the largest instruction index");
+    }
+}
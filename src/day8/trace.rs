@@ -0,0 +1,124 @@
+use super::Instruction;
+use super::isa::REGISTERS;
+use super::vm::Word;
+use crate::output::json_string;
+use std::collections::VecDeque;
+
+pub const DEFAULT_TRACE_SIZE: usize = 10_000;
+
+// State of the computer before and after a single instruction. Input read
+// and output written by the instruction are kept so the step can be undone.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Step {
+    // number of the step since the program started, starting at 0
+    pub number: usize,
+    pub ip: usize,
+    pub next_ip: usize,
+    pub instruction: Instruction,
+    pub before: [Word; REGISTERS.len()],
+    pub after: [Word; REGISTERS.len()],
+    pub input: Option<Word>,
+    pub output: Option<Word>,
+}
+
+impl Step {
+    fn to_json(&self) -> String {
+        return format!("{{\"step\": {}, \"ip\": {}, \"instruction\": {}, \"acc_before\": {}, \"acc_after\": {}, \"next_ip\": {}}}",
+            self.number, self.ip, json_string(&self.instruction.to_string()), self.before[0], self.after[0], self.next_ip);
+    }
+}
+
+// Ring buffer of the most recent steps of a computer
+#[derive(Debug, Clone)]
+pub struct Tracer {
+    capacity: usize,
+    steps: VecDeque<Step>,
+    // number of steps recorded so far, including the ones dropped from the buffer
+    recorded: usize,
+}
+
+impl Tracer {
+    pub fn new(capacity: usize) -> Tracer {
+        return Tracer {
+            capacity: capacity,
+            steps: VecDeque::with_capacity(capacity.min(4096)),
+            recorded: 0,
+        };
+    }
+
+    pub fn next_number(&self) -> usize {
+        return self.recorded;
+    }
+
+    pub fn record(&mut self, step: Step) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.steps.len() == self.capacity {
+            self.steps.pop_front();
+        }
+        self.steps.push_back(step);
+        self.recorded += 1;
+    }
+
+    // removes the most recent step, used to step backwards
+    pub fn pop(&mut self) -> Option<Step> {
+        let step = self.steps.pop_back()?;
+        self.recorded -= 1;
+        return Some(step);
+    }
+
+    pub fn steps(&self) -> impl Iterator<Item = &Step> {
+        return self.steps.iter();
+    }
+
+    // one JSON object per line and step, oldest first
+    pub fn to_jsonl(&self) -> String {
+        let mut jsonl = String::new();
+        for step in &self.steps {
+            jsonl.push_str(&step.to_json());
+            jsonl.push('\n');
+        }
+        return jsonl;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day8::{Computer, Day8};
+    use crate::solver::Solver;
+
+    #[test]
+    fn ring_buffer_and_jsonl() {
+        let program = Day8::parse("acc +1\nacc +2\njmp -2").unwrap();
+        let mut computer = Computer::load_program(program);
+        computer.trace(2);
+        computer.has_infinite_loop();
+
+        let tracer = computer.tracer.as_ref().unwrap();
+        let numbers: Vec<usize> = tracer.steps().map(|step| step.number).collect();
+        assert_eq!(numbers, vec![1, 2]);
+        assert_eq!(tracer.to_jsonl(), "\
+{\"step\": 1, \"ip\": 1, \"instruction\": \"acc +2\", \"acc_before\": 1, \"acc_after\": 3, \"next_ip\": 2}
+{\"step\": 2, \"ip\": 2, \"instruction\": \"jmp -2\", \"acc_before\": 3, \"acc_after\": 3, \"next_ip\": 0}
+");
+    }
+
+    #[test]
+    fn step_back() {
+        let program = Day8::parse("in r1\nout r1\nacc +5").unwrap();
+        let mut computer = Computer::load_program(program);
+        computer.input.push_back(7);
+        computer.trace(10);
+        while computer.current().is_some() {
+            computer.next();
+        }
+        assert_eq!((computer.accumulator(), computer.output.clone()), (5, vec![7]));
+
+        while computer.step_back() {
+        }
+        assert_eq!((computer.ip, computer.registers, computer.output.len()), (0, [0; REGISTERS.len()], 0));
+        assert_eq!(computer.input, vec![7]);
+    }
+}
@@ -0,0 +1,77 @@
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+// Registers and operands are stored in the widest type, the configured
+// register width decides which values are valid.
+pub type Word = i128;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Width {
+    I16,
+    I32,
+    I64,
+    I128,
+}
+
+impl Width {
+    pub fn contains(&self, value: Word) -> bool {
+        return match self {
+            Width::I16 => i16::try_from(value).is_ok(),
+            Width::I32 => i32::try_from(value).is_ok(),
+            Width::I64 => i64::try_from(value).is_ok(),
+            Width::I128 => true,
+        };
+    }
+}
+
+impl FromStr for Width {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Width, String> {
+        match s {
+            "i16" => Ok(Width::I16),
+            "i32" => Ok(Width::I32),
+            "i64" => Ok(Width::I64),
+            "i128" => Ok(Width::I128),
+            _ => Err(format!("unknown register width '{}', expected i16, i32, i64 or i128", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum VmError {
+    // the instruction at ip produced a value that does not fit the register width
+    Overflow { ip: usize },
+    // the instruction at ip jumped before the start or past the end of the program
+    JumpOutOfBounds { ip: usize, target: Word },
+    // the instruction at ip jumped by an offset whose target can't be represented
+    JumpOverflow { ip: usize, offset: Word },
+}
+
+impl fmt::Display for VmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VmError::Overflow { ip } => write!(f, "arithmetic overflow at instruction {}", ip),
+            VmError::JumpOutOfBounds { ip, target } => {
+                write!(f, "instruction {} jumps to {}, outside of the program", ip, target)
+            }
+            VmError::JumpOverflow { ip, offset } => {
+                write!(f, "instruction {} jumps by {}, past the largest instruction index", ip, offset)
+            }
+        }
+    }
+}
+
+impl Error for VmError {}
+
+// Outcome of executing a single instruction
+#[derive(Debug, Clone, PartialEq)]
+pub enum StepResult {
+    Continue,
+    // the program left its last instruction, nothing more to execute
+    Halted,
+    // the instruction failed, the computer is left unchanged
+    Error(VmError),
+}
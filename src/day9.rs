@@ -1,7 +1,10 @@
-use crate::input;
+use crate::input::{self, InputError};
+use crate::solver::Solver;
 use std::collections::BTreeMap;
 use std::collections::btree_map::Entry;
 
+const PREAMBLE: usize = 25;
+
 struct RingBuffer {
     // current index into buffer
     index: usize,
@@ -18,7 +21,7 @@ struct RingBuffer {
 }
 
 impl RingBuffer {
-    fn fill(input: &Vec<u64>, size: usize) -> RingBuffer {
+    fn fill(input: &[u64], size: usize) -> RingBuffer {
         let mut buffer = RingBuffer {
             index: 0,
             size: size,
@@ -27,8 +30,7 @@ impl RingBuffer {
         };
 
 
-        for idx in 0..size {
-            let value = input[idx];
+        for &value in &input[0..size] {
             buffer.buffer.push(value); 
             *buffer.elements.entry(value).or_insert(0) += 1;
         }
@@ -111,46 +113,86 @@ impl RingBuffer {
     }
 }
 
-pub fn question1() -> String {
-    let filename = "input/day9.txt";
-    let input = input::lines_as::<u64>(filename);
-    let amount = 25;
-    let mut ring_buffer = RingBuffer::fill(&input, amount);
+fn first_invalid_number(input: &[u64], preamble: usize) -> Option<u64> {
+    // without a number after the preamble there is nothing to check
+    if input.len() <= preamble {
+        return None;
+    }
+    let mut ring_buffer = RingBuffer::fill(input, preamble);
 
-    for idx in amount..input.len() {
-        let val = input[idx];
+    for &val in &input[preamble..] {
         let is_valid = ring_buffer.insert_next_value(val);
         if !is_valid {
-            return format!("Day 9.1: first wrong number = {}", val);
+            return Some(val);
         }
     }
-
-    return format!("Day 9.1: All values are valid");
+    return None;
 }
 
+fn encryption_weakness(input: &[u64], preamble: usize) -> Option<u64> {
+    let invalid_number = first_invalid_number(input, preamble)?;
+    let range = contiguous_range_summing_to(input, invalid_number)?;
 
-pub fn question2() -> String {
-    let invalid_number = 373803594;
-    let filename = "input/day9.txt";
-    let input = input::lines_as::<u64>(filename);
+    let min = range.iter().min()?;
+    let max = range.iter().max()?;
+    return Some(min + max);
+}
 
+fn contiguous_range_summing_to(input: &[u64], target: u64) -> Option<&[u64]> {
     let mut lower_bound_idx = 0;
     let mut upper_bound_idx = 0;
-    let mut sum = 0;
-
     while upper_bound_idx < input.len() {
-        sum = input[lower_bound_idx..upper_bound_idx+1].iter().sum();
+        let sum: u64 = input[lower_bound_idx..upper_bound_idx+1].iter().sum();
 
-        if sum == invalid_number {
-            let min = input[lower_bound_idx..upper_bound_idx+1].iter().min().unwrap();
-            let max = input[lower_bound_idx..upper_bound_idx+1].iter().max().unwrap();
-            return format!("Day 9.2: sum of min and max of range = {}", min + max)
-        } else if sum < invalid_number{
+        if sum == target {
+            return Some(&input[lower_bound_idx..upper_bound_idx+1]);
+        } else if sum < target {
             upper_bound_idx += 1;
-        } else if sum > invalid_number {
+        } else if sum > target {
             lower_bound_idx += 1;
         }
     }
+    return None;
+}
 
-    return format!("Day 9.2: no range found");
-}
\ No newline at end of file
+pub struct Day9;
+
+impl Solver for Day9 {
+    type Input = Vec<u64>;
+    type Answer1 = u64;
+    type Answer2 = u64;
+
+    fn parse(input: &str) -> Result<Vec<u64>, InputError> {
+        return input::lines_as::<u64>(input);
+    }
+
+    fn part1(input: &Vec<u64>) -> Option<u64> {
+        return first_invalid_number(input, PREAMBLE);
+    }
+
+    fn part2(input: &Vec<u64>) -> Option<u64> {
+        return encryption_weakness(input, PREAMBLE);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &[u64] = &[
+        35, 20, 15, 25, 47, 40, 62, 55, 65, 95,
+        102, 117, 150, 182, 127, 219, 299, 277, 309, 576];
+
+    #[test]
+    fn example() {
+        assert_eq!(first_invalid_number(EXAMPLE, 5), Some(127));
+        assert_eq!(encryption_weakness(EXAMPLE, 5), Some(62));
+    }
+
+    #[test]
+    fn short_input() {
+        assert_eq!(Day9::part1(&vec![]), None);
+        assert_eq!(Day9::part2(&vec![1, 2]), None);
+        assert_eq!(first_invalid_number(&EXAMPLE[..5], 5), None);
+    }
+}
@@ -0,0 +1,324 @@
+use crate::input::{InputError, ParseError};
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
+
+// How positions outside of the grid are treated
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Edge {
+    // positions outside of the grid don't exist
+    Bounded,
+    // columns repeat to the left and right, rows are bounded
+    WrapX,
+    // rows repeat above and below, columns are bounded
+    #[allow(dead_code)]
+    WrapY,
+    // both rows and columns repeat
+    #[allow(dead_code)]
+    Toroidal,
+}
+
+// offsets (row, col) of the 4 direct neighbours
+#[allow(dead_code)]
+pub const DIRECTIONS_4: [(isize, isize); 4] = [(-1, 0), (0, 1), (1, 0), (0, -1)];
+
+// offsets (row, col) of the 8 neighbours, including diagonals
+pub const DIRECTIONS_8: [(isize, isize); 8] = [
+    (-1,  0),
+    (-1,  1),
+    (0,   1),
+    (1,   1),
+    (1,   0),
+    (1,  -1),
+    (0,  -1),
+    (-1, -1)];
+
+// Two dimensional grid stored in a single row-major buffer
+#[derive(Debug, Clone, PartialEq)]
+pub struct Grid<T> {
+    cells: Vec<T>,
+    width: usize,
+    height: usize,
+    edge: Edge,
+}
+
+impl<T> Grid<T> {
+    pub fn new(width: usize, height: usize, cells: Vec<T>, edge: Edge) -> Grid<T> {
+        assert_eq!(cells.len(), width * height, "grid size does not match number of cells");
+        return Grid {
+            cells: cells,
+            width: width,
+            height: height,
+            edge: edge,
+        };
+    }
+
+    // Parses one row per line and one cell per character. All rows need to
+    // have the same length.
+    pub fn parse(input: &str, edge: Edge) -> Result<Grid<T>, InputError>
+        where T: TryFrom<char>,
+              T::Error: Error + 'static
+    {
+        let mut cells = Vec::new();
+        let mut width = None;
+        let mut height = 0;
+
+        for (idx, line) in input.lines().enumerate() {
+            let mut row_width = 0;
+            for c in line.chars() {
+                let cell = T::try_from(c).map_err(|e| InputError::parse(idx + 1, line, e))?;
+                cells.push(cell);
+                row_width += 1;
+            }
+
+            match width {
+                None => width = Some(row_width),
+                Some(w) if w != row_width => {
+                    let error = ParseError::new(format!("expected {} cells, found {}", w, row_width));
+                    return Err(InputError::parse(idx + 1, line, error));
+                }
+                Some(_) => (),
+            }
+            height += 1;
+        }
+
+        return Ok(Grid::new(width.unwrap_or(0), height, cells, edge));
+    }
+
+    pub fn width(&self) -> usize {
+        return self.width;
+    }
+
+    pub fn height(&self) -> usize {
+        return self.height;
+    }
+
+    // Maps a position to the cell it refers to, according to the edge behaviour.
+    // Returns None if the position lies outside of a bounded axis.
+    pub fn resolve(&self, row: isize, col: isize) -> Option<(usize, usize)> {
+        let (wrap_rows, wrap_cols) = match self.edge {
+            Edge::Bounded => (false, false),
+            Edge::WrapX => (false, true),
+            Edge::WrapY => (true, false),
+            Edge::Toroidal => (true, true),
+        };
+        let row = resolve_axis(row, self.height, wrap_rows)?;
+        let col = resolve_axis(col, self.width, wrap_cols)?;
+        return Some((row, col));
+    }
+
+    pub fn get(&self, row: isize, col: isize) -> Option<&T> {
+        let (row, col) = self.resolve(row, col)?;
+        return Some(&self.cells[row * self.width + col]);
+    }
+
+    // all positions (row, col) of the grid, row by row
+    pub fn positions(&self) -> impl Iterator<Item = (usize, usize)> {
+        let width = self.width;
+        return (0..self.height).flat_map(move |row| (0..width).map(move |col| (row, col)));
+    }
+
+    pub fn cells(&self) -> impl Iterator<Item = &T> {
+        return self.cells.iter();
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        return self.cells.chunks(self.width.max(1)).take(self.height);
+    }
+
+    pub fn map<U, F>(&self, mut f: F) -> Grid<U>
+        where F: FnMut((usize, usize), &T) -> U
+    {
+        let cells = self.positions().zip(self.cells.iter()).map(|(pos, cell)| f(pos, cell)).collect();
+        return Grid::new(self.width, self.height, cells, self.edge);
+    }
+
+    #[allow(dead_code)]
+    pub fn neighbours_4(&self, row: usize, col: usize) -> Neighbours<'_, T> {
+        return self.neighbours(row, col, &DIRECTIONS_4);
+    }
+
+    pub fn neighbours_8(&self, row: usize, col: usize) -> Neighbours<'_, T> {
+        return self.neighbours(row, col, &DIRECTIONS_8);
+    }
+
+    // cells at the given offsets from (row, col), skipping positions outside of the grid
+    pub fn neighbours<'a>(&'a self, row: usize, col: usize, offsets: &'a [(isize, isize)]) -> Neighbours<'a, T> {
+        return Neighbours {
+            grid: self,
+            row: row as isize,
+            col: col as isize,
+            offsets: offsets.iter(),
+        };
+    }
+
+    // Cells seen when walking from (row, col) into the direction (drow, dcol),
+    // excluding the start. The ray ends at a bounded edge, or once it would
+    // visit the start again on a wrapping grid.
+    pub fn ray(&self, row: usize, col: usize, drow: isize, dcol: isize) -> Ray<'_, T> {
+        return Ray {
+            grid: self,
+            start: (row, col),
+            row: row as isize,
+            col: col as isize,
+            drow: drow,
+            dcol: dcol,
+            remaining: if drow == 0 && dcol == 0 { 0 } else { self.width * self.height },
+        };
+    }
+}
+
+fn resolve_axis(value: isize, size: usize, wrap: bool) -> Option<usize> {
+    if size == 0 {
+        return None;
+    }
+    if wrap {
+        return Some(value.rem_euclid(size as isize) as usize);
+    }
+    if value < 0 || value as usize >= size {
+        return None;
+    }
+    return Some(value as usize);
+}
+
+pub struct Neighbours<'a, T> {
+    grid: &'a Grid<T>,
+    row: isize,
+    col: isize,
+    offsets: std::slice::Iter<'a, (isize, isize)>,
+}
+
+impl<'a, T> Iterator for Neighbours<'a, T> {
+    type Item = ((usize, usize), &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for (drow, dcol) in self.offsets.by_ref() {
+            let pos = self.grid.resolve(self.row + drow, self.col + dcol);
+            if let Some((row, col)) = pos {
+                return Some(((row, col), &self.grid.cells[row * self.grid.width + col]));
+            }
+        }
+        return None;
+    }
+}
+
+pub struct Ray<'a, T> {
+    grid: &'a Grid<T>,
+    start: (usize, usize),
+    row: isize,
+    col: isize,
+    drow: isize,
+    dcol: isize,
+    // upper bound of steps, ensures termination on wrapping grids
+    remaining: usize,
+}
+
+impl<'a, T> Iterator for Ray<'a, T> {
+    type Item = ((usize, usize), &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        self.row += self.drow;
+        self.col += self.dcol;
+
+        match self.grid.resolve(self.row, self.col) {
+            Some(pos) if pos != self.start => {
+                return Some((pos, &self.grid.cells[pos.0 * self.grid.width + pos.1]));
+            }
+            _ => {
+                self.remaining = 0;
+                return None;
+            }
+        }
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in self.rows() {
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+            writeln!(f)?;
+        }
+        return Ok(());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn digits(edge: Edge) -> Grid<u32> {
+        return Grid::new(3, 2, vec![1, 2, 3, 4, 5, 6], edge);
+    }
+
+    #[test]
+    fn edge_behaviour() {
+        let bounded = digits(Edge::Bounded);
+        assert_eq!(bounded.get(1, 2), Some(&6));
+        assert_eq!(bounded.get(0, 3), None);
+        assert_eq!(bounded.get(-1, 0), None);
+
+        let wrap_x = digits(Edge::WrapX);
+        assert_eq!(wrap_x.get(0, 3), Some(&1));
+        assert_eq!(wrap_x.get(1, -1), Some(&6));
+        assert_eq!(wrap_x.get(2, 0), None);
+        assert_eq!(wrap_x.get(-1, 0), None);
+        assert_eq!(wrap_x.get(0, 7), Some(&2));
+
+        let wrap_y = digits(Edge::WrapY);
+        assert_eq!(wrap_y.get(2, 0), Some(&1));
+        assert_eq!(wrap_y.get(0, 3), None);
+
+        let toroidal = digits(Edge::Toroidal);
+        assert_eq!(toroidal.get(-1, -1), Some(&6));
+        assert_eq!(toroidal.get(5, 7), Some(&5));
+    }
+
+    #[test]
+    fn neighbours() {
+        let grid = digits(Edge::Bounded);
+        let values: Vec<u32> = grid.neighbours_4(0, 0).map(|(_, v)| *v).collect();
+        assert_eq!(values, vec![2, 4]);
+        let values: Vec<u32> = grid.neighbours_8(0, 1).map(|(_, v)| *v).collect();
+        assert_eq!(values, vec![3, 6, 5, 4, 1]);
+
+        // columns wrap, the row above the grid doesn't exist
+        let grid = digits(Edge::WrapX);
+        let values: Vec<u32> = grid.neighbours_8(0, 0).map(|(_, v)| *v).collect();
+        assert_eq!(values, vec![2, 5, 4, 6, 3]);
+
+        let grid = digits(Edge::Toroidal);
+        assert_eq!(grid.neighbours_8(0, 0).count(), 8);
+        let values: Vec<u32> = grid.neighbours_4(0, 0).map(|(_, v)| *v).collect();
+        assert_eq!(values, vec![4, 2, 4, 3]);
+    }
+
+    #[test]
+    fn ray_cast() {
+        let grid = digits(Edge::Bounded);
+        let values: Vec<u32> = grid.ray(0, 0, 0, 1).map(|(_, v)| *v).collect();
+        assert_eq!(values, vec![2, 3]);
+
+        // stops before reaching the start again
+        let grid = digits(Edge::WrapX);
+        let values: Vec<u32> = grid.ray(1, 1, 0, 1).map(|(_, v)| *v).collect();
+        assert_eq!(values, vec![6, 4]);
+        assert_eq!(grid.ray(0, 0, 0, 0).count(), 0);
+    }
+
+    #[test]
+    fn parse_and_display() {
+        let grid = Grid::<char>::parse("ab\ncd\n", Edge::Bounded).unwrap();
+        assert_eq!((grid.width(), grid.height()), (2, 2));
+        assert_eq!(grid.get(1, 0), Some(&'c'));
+        assert_eq!(grid.to_string(), "ab\ncd\n");
+
+        let error = Grid::<char>::parse("ab\nc", Edge::Bounded).unwrap_err();
+        assert_eq!(error.to_string(), "<input>:2: expected 2 cells, found 1 (in 'c')");
+    }
+}
@@ -1,41 +1,267 @@
+use std::error::Error;
+use std::fmt;
 use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::io::{self, IsTerminal, Read};
+use std::num::ParseIntError;
+use std::str::FromStr;
 
-pub fn lines_as<T>(filename: &str) -> Vec<T>
-    where T: std::str::FromStr,
-          T::Err: std::fmt::Debug
+// Error raised while loading or parsing a puzzle input. Parse errors point to
+// the 1-based line of the offending text, and to the file once it is known.
+#[derive(Debug)]
+pub enum InputError {
+    Read {
+        path: String,
+        error: io::Error,
+    },
+    Parse {
+        path: Option<String>,
+        line: usize,
+        text: String,
+        error: Box<dyn Error>,
+    },
+}
+
+impl InputError {
+    pub fn parse<E>(line: usize, text: &str, error: E) -> InputError
+        where E: Into<Box<dyn Error>>
+    {
+        return InputError::Parse {
+            path: None,
+            line: line,
+            text: text.to_string(),
+            error: error.into(),
+        };
+    }
+
+    // attaches the file the input was read from
+    pub fn in_file(self, filename: &str) -> InputError {
+        match self {
+            InputError::Parse { line, text, error, .. } => InputError::Parse {
+                path: Some(filename.to_string()),
+                line: line,
+                text: text,
+                error: error,
+            },
+            error => error,
+        }
+    }
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputError::Read { path, error } => write!(f, "could not read {}: {}", path, error),
+            InputError::Parse { path, line, text, error } => {
+                let path = path.as_deref().unwrap_or("<input>");
+                write!(f, "{}:{}: {} (in '{}')", path, line, error, text)
+            }
+        }
+    }
+}
+
+impl Error for InputError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            InputError::Read { error, .. } => Some(error),
+            InputError::Parse { error, .. } => Some(error.as_ref()),
+        }
+    }
+}
+
+// Error for the hand written line parsers of the individual days.
+#[derive(Debug)]
+pub struct ParseError(String);
+
+impl ParseError {
+    pub fn new(message: String) -> ParseError {
+        return ParseError(message);
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Error for ParseError {}
+
+impl From<ParseIntError> for ParseError {
+    fn from(error: ParseIntError) -> ParseError {
+        return ParseError(error.to_string());
+    }
+}
+
+// Where the puzzle input is read from
+pub enum Source {
+    File(String),
+    Stdin,
+    // in-memory input, the name is used for error messages
+    Inline { name: String, input: String },
+}
+
+impl Source {
+    // the checked in input of the given day
+    pub fn day(day: u32) -> Source {
+        return Source::File(format!("input/day{}.txt", day));
+    }
+
+    pub fn inline(name: &str, input: &str) -> Source {
+        return Source::Inline {
+            name: name.to_string(),
+            input: input.to_string(),
+        };
+    }
+
+    // `-` is used for stdin, everything else is a file path
+    pub fn from_arg(arg: &str) -> Source {
+        if arg == "-" {
+            return Source::Stdin;
+        }
+        return Source::File(arg.to_string());
+    }
+
+    // Input piped into the program, read upfront. Returns None if stdin is a
+    // terminal or nothing was piped in (e.g. when started with </dev/null).
+    pub fn piped_stdin() -> Result<Option<Source>, InputError> {
+        if io::stdin().is_terminal() {
+            return Ok(None);
+        }
+        let input = Source::Stdin.read()?;
+        if input.is_empty() {
+            return Ok(None);
+        }
+        return Ok(Some(Source::inline("<stdin>", &input)));
+    }
+
+    // name used in error messages
+    pub fn name(&self) -> &str {
+        match self {
+            Source::File(path) => path,
+            Source::Stdin => "<stdin>",
+            Source::Inline { name, .. } => name,
+        }
+    }
+
+    pub fn read(&self) -> Result<String, InputError> {
+        match self {
+            Source::File(path) => {
+                let file = File::open(path).map_err(|error| InputError::Read {
+                    path: path.clone(),
+                    error: error,
+                })?;
+                return read_from(file, self.name());
+            }
+            Source::Stdin => return read_from(io::stdin().lock(), self.name()),
+            Source::Inline { input, .. } => return Ok(input.clone()),
+        }
+    }
+}
+
+// reads the complete input from any reader, `name` is used for error messages
+pub fn read_from<R: Read>(mut reader: R, name: &str) -> Result<String, InputError> {
+    let mut input = String::new();
+    reader.read_to_string(&mut input).map_err(|error| InputError::Read {
+        path: name.to_string(),
+        error: error,
+    })?;
+    return Ok(input);
+}
+
+// parses every line with the given function
+pub fn lines_with<T, E, F>(input: &str, parse: F) -> Result<Vec<T>, InputError>
+    where F: Fn(&str) -> Result<T, E>,
+          E: Error + 'static
 {
-    let file = File::open(filename).unwrap();
-    let reader = BufReader::new(file);
-    let mut input = Vec::new();
+    let mut values = Vec::new();
 
-    for line in reader.lines() {
-        let value = line.unwrap().parse::<T>().unwrap();
-        input.push(value);
+    for (idx, line) in input.lines().enumerate() {
+        let value = parse(line).map_err(|e| InputError::parse(idx + 1, line, e))?;
+        values.push(value);
     }
-    return input;
+    return Ok(values);
 }
 
-pub fn collect_groups_as<T>(filename: &str) -> Vec<Vec<T>>
-    where T: std::str::FromStr,
-          T::Err: std::fmt::Debug
+pub fn lines_as<T>(input: &str) -> Result<Vec<T>, InputError>
+    where T: FromStr,
+          T::Err: Error + 'static
+{
+    return lines_with(input, |line| line.parse::<T>());
+}
+
+// Lines holding nothing but whitespace separate groups, just like empty ones
+fn is_separator(line: &str) -> bool {
+    return line.trim().is_empty();
+}
+
+// parsed lines of a group and the 1-based numbers of its first and last line
+pub type Group<T> = (Vec<T>, (usize, usize));
+
+// Groups of lines separated by blank lines. Several blank lines in a row, and
+// blank lines at the start or the end, don't produce empty groups.
+pub fn collect_groups_as<T>(input: &str) -> Result<Vec<Group<T>>, InputError>
+    where T: FromStr,
+          T::Err: Error + 'static
     {
     let mut groups = Vec::new();
-    let file = File::open(filename).unwrap();
-    let reader = BufReader::new(file);
 
     let mut current_group = Vec::new();
-    for line in reader.lines() {
-        let l = line.unwrap();
-
-        if l == String::from("") {
-            groups.push(current_group);
-            current_group = Vec::new();
+    let mut first_line = 0;
+    for (idx, line) in input.lines().enumerate() {
+        if is_separator(line) {
+            if !current_group.is_empty() {
+                groups.push((current_group, (first_line, idx)));
+                current_group = Vec::new();
+            }
         } else {
-            current_group.push(l.parse::<T>().unwrap());
+            let value = line.parse::<T>().map_err(|e| InputError::parse(idx + 1, line, e))?;
+            if current_group.is_empty() {
+                first_line = idx + 1;
+            }
+            current_group.push(value);
         }
     }
-    groups.push(current_group);
-    
-    return groups;
-}
\ No newline at end of file
+    if !current_group.is_empty() {
+        let last_line = first_line + current_group.len() - 1;
+        groups.push((current_group, (first_line, last_line)));
+    }
+
+    return Ok(groups);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_error_points_to_line() {
+        let error = lines_as::<u32>("1\n2\nthree\n4").unwrap_err().in_file("numbers.txt");
+        match error {
+            InputError::Parse { path, line, text, .. } => {
+                assert_eq!(path.as_deref(), Some("numbers.txt"));
+                assert_eq!(line, 3);
+                assert_eq!(text, "three");
+            }
+            error => panic!("unexpected error {}", error),
+        }
+    }
+
+    #[test]
+    fn groups_are_separated_by_blank_lines() {
+        let groups = collect_groups_as::<u32>("1\n2\n\n3").unwrap();
+        assert_eq!(groups, vec![(vec![1, 2], (1, 2)), (vec![3], (4, 4))]);
+
+        let input = "\n1\r\n2\r\n \t\r\n\n\n3\n\n";
+        assert_eq!(collect_groups_as::<u32>(input).unwrap(), vec![(vec![1, 2], (2, 3)), (vec![3], (7, 7))]);
+
+        let error = collect_groups_as::<u32>("1\n\nx").unwrap_err();
+        assert_eq!(error.to_string(), "<input>:3: invalid digit found in string (in 'x')");
+    }
+
+    #[test]
+    fn inline_source() {
+        let source = Source::inline("example", "1\n2");
+        assert_eq!(source.name(), "example");
+        assert_eq!(lines_as::<u32>(&source.read().unwrap()).unwrap(), vec![1, 2]);
+    }
+}
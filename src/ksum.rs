@@ -0,0 +1,233 @@
+// Finds k entries of a list that add up to a target.
+//
+// Matches are reported as index tuples in ascending order. Depending on k a
+// different strategy is used: a hash map for pairs, sorting and two pointers
+// for triples, and meet-in-the-middle over the combinations of both halves
+// for larger k.
+
+use std::collections::HashMap;
+
+// Whether the same entry may be used several times in a match
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Reuse {
+    Forbidden,
+    Allowed,
+}
+
+// All matches, sorted
+#[allow(dead_code)]
+pub fn find_k_sum(values: &[i64], k: usize, target: i64, reuse: Reuse) -> Vec<Vec<usize>> {
+    let mut matches = Vec::new();
+    search(values, k, target as i128, reuse, &mut |indices| {
+        matches.push(indices);
+        true
+    });
+    matches.sort();
+    return matches;
+}
+
+// The first match found by the search, not necessarily the smallest tuple
+pub fn find_first_k_sum(values: &[i64], k: usize, target: i64, reuse: Reuse) -> Option<Vec<usize>> {
+    let mut first = None;
+    search(values, k, target as i128, reuse, &mut |indices| {
+        first = Some(indices);
+        false
+    });
+    return first;
+}
+
+// Calls `emit` for every match until it returns false. Sums are computed as
+// i128, so they can't overflow for any k that fits into memory.
+fn search(values: &[i64], k: usize, target: i128, reuse: Reuse, emit: &mut dyn FnMut(Vec<usize>) -> bool) {
+    match k {
+        0 => {
+            if target == 0 {
+                emit(Vec::new());
+            }
+        }
+        1 => {
+            for (index, value) in values.iter().enumerate() {
+                if *value as i128 == target && !emit(vec![index]) {
+                    return;
+                }
+            }
+        }
+        2 => two_sum(values, target, reuse, emit),
+        3 => three_sum(values, target, reuse, emit),
+        _ => meet_in_the_middle(values, k, target, reuse, emit),
+    }
+}
+
+fn two_sum(values: &[i64], target: i128, reuse: Reuse, emit: &mut dyn FnMut(Vec<usize>) -> bool) {
+    // indices of the values seen so far
+    let mut seen: HashMap<i128, Vec<usize>> = HashMap::new();
+    for (j, value) in values.iter().enumerate() {
+        let value = *value as i128;
+        if reuse == Reuse::Allowed {
+            seen.entry(value).or_default().push(j);
+        }
+        if let Some(indices) = seen.get(&(target - value)) {
+            for i in indices {
+                if !emit(vec![*i, j]) {
+                    return;
+                }
+            }
+        }
+        if reuse == Reuse::Forbidden {
+            seen.entry(value).or_default().push(j);
+        }
+    }
+}
+
+fn three_sum(values: &[i64], target: i128, reuse: Reuse, emit: &mut dyn FnMut(Vec<usize>) -> bool) {
+    let mut sorted: Vec<usize> = (0..values.len()).collect();
+    sorted.sort_by_key(|index| values[*index]);
+    let value = |position: usize| values[sorted[position]] as i128;
+    let n = sorted.len();
+    let gap = if reuse == Reuse::Allowed { 0 } else { 1 };
+
+    // positions in sorted order, mapped back to ascending indices
+    let mut report = |positions: [usize; 3]| {
+        let mut indices: Vec<usize> = positions.iter().map(|p| sorted[*p]).collect();
+        indices.sort_unstable();
+        return emit(indices);
+    };
+
+    for first in 0..n {
+        let need = target - value(first);
+        let mut lo = first + gap;
+        let mut hi = n - 1;
+        while lo + gap <= hi && hi < n {
+            let sum = value(lo) + value(hi);
+            if sum < need {
+                lo += 1;
+            } else if sum > need {
+                if hi == 0 {
+                    break;
+                }
+                hi -= 1;
+            } else if value(lo) == value(hi) {
+                // every pair within the run of equal values matches
+                for a in lo..=hi {
+                    for b in (a + gap)..=hi {
+                        if !report([first, a, b]) {
+                            return;
+                        }
+                    }
+                }
+                break;
+            } else {
+                let lo_end = (lo..=hi).take_while(|p| value(*p) == value(lo)).last().unwrap();
+                let hi_start = (lo..=hi).rev().take_while(|p| value(*p) == value(hi)).last().unwrap();
+                for a in lo..=lo_end {
+                    for b in hi_start..=hi {
+                        if !report([first, a, b]) {
+                            return;
+                        }
+                    }
+                }
+                lo = lo_end + 1;
+                hi = hi_start - 1;
+            }
+        }
+    }
+}
+
+// Calls `f` with every ascending (or, with reuse, non-decreasing) index
+// combination of the given size and its sum. Stops once `f` returns false.
+fn combinations(values: &[i64], size: usize, reuse: Reuse, start: usize, current: &mut Vec<usize>, sum: i128,
+                f: &mut dyn FnMut(&[usize], i128) -> bool) -> bool
+{
+    if current.len() == size {
+        return f(current, sum);
+    }
+    for index in start..values.len() {
+        current.push(index);
+        let next = if reuse == Reuse::Allowed { index } else { index + 1 };
+        let go_on = combinations(values, size, reuse, next, current, sum + values[index] as i128, f);
+        current.pop();
+        if !go_on {
+            return false;
+        }
+    }
+    return true;
+}
+
+// Splits every match into its first k/2 and its remaining indices. The right
+// halves are grouped by their sum, so each left half only needs a lookup.
+fn meet_in_the_middle(values: &[i64], k: usize, target: i128, reuse: Reuse, emit: &mut dyn FnMut(Vec<usize>) -> bool) {
+    let (left_size, right_size) = (k / 2, k - k / 2);
+
+    let mut right_halves: HashMap<i128, Vec<Vec<usize>>> = HashMap::new();
+    combinations(values, right_size, reuse, 0, &mut Vec::new(), 0, &mut |indices, sum| {
+        right_halves.entry(sum).or_default().push(indices.to_vec());
+        true
+    });
+
+    combinations(values, left_size, reuse, 0, &mut Vec::new(), 0, &mut |left, sum| {
+        let last = left[left.len() - 1];
+        let right_halves = match right_halves.get(&(target - sum)) {
+            Some(right_halves) => right_halves,
+            None => return true,
+        };
+        for right in right_halves {
+            let joins = match reuse {
+                Reuse::Allowed => right[0] >= last,
+                Reuse::Forbidden => right[0] > last,
+            };
+            if joins && !emit(left.iter().chain(right.iter()).copied().collect()) {
+                return false;
+            }
+        }
+        return true;
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn brute_force(values: &[i64], k: usize, target: i64, reuse: Reuse) -> Vec<Vec<usize>> {
+        let mut matches = Vec::new();
+        combinations(values, k, reuse, 0, &mut Vec::new(), 0, &mut |indices, sum| {
+            if sum == target as i128 {
+                matches.push(indices.to_vec());
+            }
+            true
+        });
+        matches.sort();
+        return matches;
+    }
+
+    #[test]
+    fn matches_brute_force() {
+        // small values with many duplicates, to get lots of matches
+        let mut seed: u64 = 7;
+        let values: Vec<i64> = (0..14)
+            .map(|_| {
+                seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+                (seed >> 59) as i64 - 8
+            })
+            .collect();
+
+        for k in 1..=5 {
+            for target in -6..=6 {
+                for reuse in &[Reuse::Forbidden, Reuse::Allowed] {
+                    let expected = brute_force(&values, k, target, *reuse);
+                    assert_eq!(find_k_sum(&values, k, target, *reuse), expected, "k = {}, target = {}", k, target);
+                    if let Some(first) = find_first_k_sum(&values, k, target, *reuse) {
+                        assert!(expected.contains(&first));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn reuse() {
+        assert_eq!(find_k_sum(&[1010, 3], 2, 2020, Reuse::Forbidden), Vec::<Vec<usize>>::new());
+        assert_eq!(find_k_sum(&[1010, 3], 2, 2020, Reuse::Allowed), vec![vec![0, 0]]);
+        assert_eq!(find_k_sum(&[5, 1], 0, 0, Reuse::Forbidden), vec![Vec::<usize>::new()]);
+        assert_eq!(find_first_k_sum(&[i64::MAX, i64::MAX, 2], 2, -2, Reuse::Forbidden), None);
+    }
+}
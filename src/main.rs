@@ -1,21 +1,302 @@
+// the code base prefers explicit returns and field names
+#![allow(clippy::needless_return, clippy::redundant_field_names)]
+
 #[macro_use]
 extern crate lazy_static;
+mod bench;
+mod grid;
 mod input;
-// mod day1;
-// mod day2;
-// mod day3;
-// mod day4;
-// mod day5;
-// mod day6;
-// mod day7;
-// mod day8;
-// mod day9;
-// mod day10;
-// mod day11;
+mod ksum;
+mod output;
+mod solver;
+mod day1;
+mod day2;
+mod day3;
+mod day4;
+mod day5;
+mod day6;
+mod day7;
+mod day8;
+mod day9;
+mod day10;
+mod day11;
 mod day12;
 
+use input::Source;
+use output::{Format, Record};
+use bench::Timing;
+use solver::Day;
+use std::env;
+use std::process;
+
+// registry of all solved days
+const DAYS: &[Day] = &[
+    solver::day::<day1::Day1>(1),
+    solver::day::<day2::Day2>(2).with_tool(day2::tool),
+    solver::day::<day3::Day3>(3).with_tool(day3::tool),
+    solver::day::<day4::Day4>(4).with_tool(day4::tool),
+    solver::day::<day5::Day5>(5),
+    solver::day::<day6::Day6>(6),
+    solver::day::<day7::Day7>(7),
+    solver::day::<day8::Day8>(8).with_tool(day8::tool),
+    solver::day::<day9::Day9>(9),
+    solver::day::<day10::Day10>(10),
+    solver::day::<day11::Day11>(11),
+    solver::day::<day12::Day12>(12),
+];
+
+const USAGE: &str = "\
+usage: aoc <day> [part] [--input <file>|-] [--format text|json|csv]
+       aoc 1 [--target <n>] [--count <k>] [--input <file>|-] [--format text|json|csv]
+       aoc <day> <tool> [args...] [--input <file>]
+       aoc all [--format text|json|csv]
+       aoc bench [day] [--iterations <n>] [--format text|json|csv]";
+
+const DEFAULT_ITERATIONS: usize = 10;
+
+enum Selection {
+    All,
+    Day(u32, Option<u32>),
+    Bench(Option<u32>),
+    Tool(u32, String, Vec<String>),
+}
+
+struct Options {
+    positional: Vec<String>,
+    input: Option<Source>,
+    iterations: Option<usize>,
+    format: Option<Format>,
+    // day 1 only: sum and number of the entries to look for
+    target: Option<i64>,
+    count: Option<usize>,
+}
+
+fn parse_options(args: &[String]) -> Result<Options, String> {
+    let mut options = Options {
+        positional: Vec::new(),
+        input: None,
+        iterations: None,
+        format: None,
+        target: None,
+        count: None,
+    };
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--input" | "-i" => {
+                let value = args.next().ok_or(format!("missing value for {}", arg))?;
+                options.input = Some(Source::from_arg(value));
+            }
+            "--iterations" | "-n" => {
+                let value = args.next().ok_or(format!("missing value for {}", arg))?;
+                let iterations = value.parse::<usize>().ok().filter(|n| *n > 0)
+                    .ok_or(format!("invalid number of iterations '{}', expected at least 1", value))?;
+                options.iterations = Some(iterations);
+            }
+            "--format" | "-f" => {
+                let value = args.next().ok_or(format!("missing value for {}", arg))?;
+                options.format = Some(value.parse::<Format>()?);
+            }
+            "--target" => {
+                let value = args.next().ok_or(format!("missing value for {}", arg))?;
+                let target = value.parse::<i64>()
+                    .map_err(|_| format!("invalid target '{}'", value))?;
+                options.target = Some(target);
+            }
+            "--count" => {
+                let value = args.next().ok_or(format!("missing value for {}", arg))?;
+                let count = value.parse::<usize>().ok().filter(|n| *n > 0)
+                    .ok_or(format!("invalid count '{}', expected at least 1", value))?;
+                options.count = Some(count);
+            }
+            _ => options.positional.push(arg.clone()),
+        }
+    }
+    return Ok(options);
+}
+
+fn parse_args(args: &[String]) -> Result<Selection, String> {
+    match args {
+        [all] if all == "all" => Ok(Selection::All),
+        [bench] if bench == "bench" => Ok(Selection::Bench(None)),
+        [bench, day] if bench == "bench" => Ok(Selection::Bench(Some(parse_day(day)?))),
+        [day] => Ok(Selection::Day(parse_day(day)?, None)),
+        [day, tool, args @ ..] if tool.parse::<u32>().is_err() => {
+            Ok(Selection::Tool(parse_day(day)?, tool.clone(), args.to_vec()))
+        }
+        [day, part] => Ok(Selection::Day(parse_day(day)?, Some(parse_part(part)?))),
+        _ => Err(String::from(USAGE)),
+    }
+}
+
+fn parse_day(day: &str) -> Result<u32, String> {
+    return day.parse::<u32>().map_err(|_| format!("invalid day '{}'\n{}", day, USAGE));
+}
+
+fn parse_part(part: &str) -> Result<u32, String> {
+    match part.parse::<u32>() {
+        Ok(p) if p == 1 || p == 2 => Ok(p),
+        _ => Err(format!("invalid part '{}', expected 1 or 2", part)),
+    }
+}
+
+fn find_day(day: u32) -> Result<&'static Day, String> {
+    return DAYS.iter()
+        .find(|d| d.day == day)
+        .ok_or(format!("day {} is not solved yet", day));
+}
+
+// explicit --input, piped stdin or the checked in input, in that order
+fn source_for(day: u32, input: Option<Source>) -> Result<Source, String> {
+    match input {
+        Some(source) => Ok(source),
+        None => {
+            let piped = Source::piped_stdin().map_err(|e| e.to_string())?;
+            Ok(piped.unwrap_or_else(|| Source::day(day)))
+        }
+    }
+}
+
+// warnings about the input are printed to stderr
+fn run_day(day: &Day, part: Option<u32>, source: &Source) -> Result<Vec<Record>, String> {
+    let input = source.read().map_err(|e| e.to_string())?;
+
+    let parts = match part {
+        Some(p) => vec![p],
+        None => vec![1, 2],
+    };
+    let (records, warnings) = (day.solve)(day.day, &input, &parts).map_err(|e| e.in_file(source.name()).to_string())?;
+    for (line, message) in warnings {
+        eprintln!("warning: {}:{}: {}", source.name(), line, message);
+    }
+    return Ok(records);
+}
+
+fn bench_day(day: &Day, source: &Source, iterations: usize) -> Result<(u32, Vec<Timing>), String> {
+    let input = source.read().map_err(|e| e.to_string())?;
+    let timings = (day.bench)(&input, iterations).map_err(|e| e.in_file(source.name()).to_string())?;
+    return Ok((day.day, timings));
+}
+
+fn run(options: Options) -> Result<(), String> {
+    let selection = parse_args(&options.positional)?;
+    let is_single_day = match selection {
+        Selection::All | Selection::Bench(None) => false,
+        Selection::Day(_, _) | Selection::Bench(Some(_)) | Selection::Tool(_, _, _) => true,
+    };
+    if options.input.is_some() && !is_single_day {
+        return Err(String::from("--input can only be used for a single day"));
+    }
+    if !matches!(selection, Selection::Bench(_)) && options.iterations.is_some() {
+        return Err(String::from("--iterations can only be used with bench"));
+    }
+    let reconciles = options.target.is_some() || options.count.is_some();
+    if reconciles && !matches!(selection, Selection::Day(1, None)) {
+        return Err(String::from("--target and --count can only be used with day 1 and no part"));
+    }
+
+    let format = options.format.unwrap_or(Format::Text);
+    let iterations = options.iterations.unwrap_or(DEFAULT_ITERATIONS);
+
+    let output = match selection {
+        Selection::All => {
+            let mut records = Vec::new();
+            for day in DAYS {
+                records.extend(run_day(day, None, &Source::day(day.day))?);
+            }
+            output::render_records(&records, format)
+        }
+        Selection::Day(day, None) if reconciles => {
+            let target = options.target.unwrap_or(day1::TARGET);
+            let count = options.count.unwrap_or(day1::COUNT);
+            day1::reconcile(&source_for(day, options.input)?, target, count, format)?
+        }
+        Selection::Day(day, part) => {
+            let records = run_day(find_day(day)?, part, &source_for(day, options.input)?)?;
+            output::render_records(&records, format)
+        }
+        Selection::Bench(None) => {
+            let mut timings = Vec::new();
+            for day in DAYS {
+                timings.push(bench_day(day, &Source::day(day.day), iterations)?);
+            }
+            bench::render_timings(&timings, format)
+        }
+        Selection::Bench(Some(day)) => {
+            let timings = bench_day(find_day(day)?, &source_for(day, options.input)?, iterations)?;
+            bench::render_timings(&[timings], format)
+        }
+        Selection::Tool(day, name, args) => {
+            // tools may read commands from stdin, so the input is never taken from there
+            let tool = find_day(day)?.tool.ok_or(format!("day {} has no tools", day))?;
+            let source = options.input.unwrap_or_else(|| Source::day(day));
+            return tool(&name, &args, &source, format);
+        }
+    };
+    print!("{}", output);
+    return Ok(());
+}
 
 fn main() {
-    println!("{}", day12::question1());
-    println!("{}", day12::question2());
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    if let Err(message) = parse_options(&args).and_then(run) {
+        eprintln!("error: {}", message);
+        process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // known answers for the checked in inputs: (day, part 1, part 2)
+    const ANSWERS: &[(u32, &str, &str)] = &[
+        (1, "63616", "67877784"),
+        (2, "538", "489"),
+        (3, "286", "3638606400"),
+        (4, "200", "116"),
+        (5, "894", "579"),
+        (6, "6387", "3039"),
+        (7, "261", "3765"),
+        (8, "1420", "1245"),
+        (9, "373803594", "51152360"),
+        (10, "2400", "338510590509056"),
+        (11, "2438", "2174"),
+        (12, "1603", "52866"),
+    ];
+
+    #[test]
+    fn every_day_has_known_answers() {
+        let days: Vec<u32> = DAYS.iter().map(|d| d.day).collect();
+        let known: Vec<u32> = ANSWERS.iter().map(|(day, _, _)| *day).collect();
+        assert_eq!(days, known);
+    }
+
+    #[test]
+    fn options() {
+        let args = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<String>>();
+        let options = parse_options(&args(&["bench", "9", "-n", "3"])).unwrap();
+        assert_eq!((options.positional, options.iterations), (args(&["bench", "9"]), Some(3)));
+        assert_eq!(parse_options(&args(&["bench", "-n", "0"])).err(),
+            Some(String::from("invalid number of iterations '0', expected at least 1")));
+        assert_eq!(parse_options(&args(&["1", "--count", "0"])).err(),
+            Some(String::from("invalid count '0', expected at least 1")));
+    }
+
+    #[test]
+    fn checked_in_inputs() {
+        for (day, part1, part2) in ANSWERS {
+            let input = Source::day(*day).read().unwrap();
+            let (records, warnings) = (find_day(*day).unwrap().solve)(*day, &input, &[1, 2]).unwrap();
+            assert!(warnings.is_empty(), "day {}", day);
+
+            let answers: Vec<(u32, Option<String>)> = records.iter()
+                .map(|r| (r.part, r.answer.as_ref().map(|a| a.to_string())))
+                .collect();
+            let expected = vec![(1, Some(part1.to_string())), (2, Some(part2.to_string()))];
+            assert_eq!(answers, expected, "day {}", day);
+        }
+    }
 }
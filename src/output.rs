@@ -0,0 +1,203 @@
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+// output format of the command line tool
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Text,
+    Json,
+    Csv,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(format: &str) -> Result<Format, String> {
+        match format {
+            "text" => Ok(Format::Text),
+            "json" => Ok(Format::Json),
+            "csv" => Ok(Format::Csv),
+            _ => Err(format!("unknown format '{}', expected text, json or csv", format)),
+        }
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Format::Text => write!(f, "text"),
+            Format::Json => write!(f, "json"),
+            Format::Csv => write!(f, "csv"),
+        }
+    }
+}
+
+// For commands that only print text, an error if another format is requested
+pub fn text_only(command: &str, format: Format) -> Result<(), String> {
+    match format {
+        Format::Text => Ok(()),
+        _ => Err(format!("{} only prints text, not {}", command, format)),
+    }
+}
+
+// Typed answer of a puzzle part. Numbers are kept as numbers, so they can be
+// compared and rendered without quotes in JSON.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Answer {
+    Signed(i128),
+    Unsigned(u128),
+    Text(String),
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Answer::Signed(value) => write!(f, "{}", value),
+            Answer::Unsigned(value) => write!(f, "{}", value),
+            Answer::Text(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+macro_rules! answer_from {
+    ($variant:ident, $($t:ty),*) => {
+        $(
+            impl From<$t> for Answer {
+                fn from(value: $t) -> Answer {
+                    return Answer::$variant(value.into());
+                }
+            }
+        )*
+    };
+}
+
+answer_from!(Signed, i8, i16, i32, i64, i128);
+answer_from!(Unsigned, u8, u16, u32, u64, u128);
+
+impl From<usize> for Answer {
+    fn from(value: usize) -> Answer {
+        return Answer::Unsigned(value as u128);
+    }
+}
+
+impl From<String> for Answer {
+    fn from(value: String) -> Answer {
+        return Answer::Text(value);
+    }
+}
+
+// answer of a single part, None if no solution was found
+#[derive(Debug)]
+pub struct Record {
+    pub day: u32,
+    pub part: u32,
+    pub answer: Option<Answer>,
+    pub elapsed: Duration,
+}
+
+pub fn json_string(value: &str) -> String {
+    let mut escaped = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    return escaped;
+}
+
+pub fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        return format!("\"{}\"", value.replace('"', "\"\""));
+    }
+    return value.to_string();
+}
+
+fn json_answer(answer: &Option<Answer>) -> String {
+    match answer {
+        Some(Answer::Text(value)) => json_string(value),
+        Some(answer) => answer.to_string(),
+        None => String::from("null"),
+    }
+}
+
+pub fn render_records(records: &[Record], format: Format) -> String {
+    let mut out = String::new();
+    match format {
+        Format::Text => {
+            for record in records {
+                match &record.answer {
+                    Some(answer) => out.push_str(&format!("Day {}.{}: {}\n", record.day, record.part, answer)),
+                    None => out.push_str(&format!("Day {}.{}: no solution found\n", record.day, record.part)),
+                }
+            }
+        }
+        Format::Json => {
+            let entries: Vec<String> = records.iter().map(|record| format!(
+                "  {{\"day\": {}, \"part\": {}, \"answer\": {}, \"elapsed_ns\": {}}}",
+                record.day, record.part, json_answer(&record.answer), record.elapsed.as_nanos()
+            )).collect();
+            out.push_str(&format!("[\n{}\n]\n", entries.join(",\n")));
+        }
+        Format::Csv => {
+            out.push_str("day,part,answer,elapsed_ns\n");
+            for record in records {
+                let answer = record.answer.as_ref().map(|a| a.to_string()).unwrap_or_default();
+                out.push_str(&format!("{},{},{},{}\n",
+                    record.day, record.part, csv_field(&answer), record.elapsed.as_nanos()));
+            }
+        }
+    }
+    return out;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn records() -> Vec<Record> {
+        return vec![
+            Record { day: 3, part: 1, answer: Some(Answer::from(286u64)), elapsed: Duration::from_nanos(1200) },
+            Record { day: 3, part: 2, answer: Some(Answer::from(String::from("a, \"b\""))), elapsed: Duration::from_nanos(5) },
+            Record { day: 9, part: 1, answer: None, elapsed: Duration::from_nanos(7) },
+        ];
+    }
+
+    #[test]
+    fn render_text() {
+        assert_eq!(render_records(&records(), Format::Text),
+            "Day 3.1: 286\nDay 3.2: a, \"b\"\nDay 9.1: no solution found\n");
+    }
+
+    #[test]
+    fn render_json() {
+        assert_eq!(render_records(&records(), Format::Json), "[
+  {\"day\": 3, \"part\": 1, \"answer\": 286, \"elapsed_ns\": 1200},
+  {\"day\": 3, \"part\": 2, \"answer\": \"a, \\\"b\\\"\", \"elapsed_ns\": 5},
+  {\"day\": 9, \"part\": 1, \"answer\": null, \"elapsed_ns\": 7}
+]
+");
+    }
+
+    #[test]
+    fn render_csv() {
+        assert_eq!(render_records(&records(), Format::Csv),
+            "day,part,answer,elapsed_ns\n3,1,286,1200\n3,2,\"a, \"\"b\"\"\",5\n9,1,,7\n");
+    }
+
+    #[test]
+    fn formats() {
+        for format in &[Format::Text, Format::Json, Format::Csv] {
+            assert_eq!(format.to_string().parse::<Format>(), Ok(*format));
+        }
+        assert_eq!(text_only("aoc 4 report", Format::Text), Ok(()));
+        assert_eq!(text_only("aoc 4 report", Format::Json), Err(String::from("aoc 4 report only prints text, not json")));
+    }
+}
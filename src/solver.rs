@@ -0,0 +1,83 @@
+use crate::bench::{bench, Timing};
+use crate::input::{InputError, Source};
+use crate::output::{Answer, Format, Record};
+use std::time::Instant;
+
+// line number and message of a problem in the input
+pub type Warning = (usize, String);
+
+// A puzzle solution, split into a parsing stage and the two parts of the puzzle.
+// The input is parsed once and shared by both parts.
+pub trait Solver {
+    type Input;
+    type Answer1: Into<Answer>;
+    type Answer2: Into<Answer>;
+
+    fn parse(input: &str) -> Result<Self::Input, InputError>;
+
+    // problems in the parsed input that don't stop the solver, the caller
+    // reports them once so parsing stays silent
+    fn warnings(_input: &Self::Input) -> Vec<Warning> {
+        return Vec::new();
+    }
+
+    // both parts return None if no solution can be found for the given input
+    fn part1(input: &Self::Input) -> Option<Self::Answer1>;
+    fn part2(input: &Self::Input) -> Option<Self::Answer2>;
+}
+
+// Parses the input and runs the requested parts of a solver, returning the
+// answer for each part together with the time it took to compute it, and the
+// warnings about the input.
+pub fn solve<S: Solver>(day: u32, input: &str, parts: &[u32]) -> Result<(Vec<Record>, Vec<Warning>), InputError> {
+    let parsed = S::parse(input)?;
+    let mut records = Vec::new();
+
+    for part in parts {
+        let start = Instant::now();
+        let answer = match part {
+            1 => S::part1(&parsed).map(|a| a.into()),
+            2 => S::part2(&parsed).map(|a| a.into()),
+            _ => None,
+        };
+        records.push(Record {
+            day: day,
+            part: *part,
+            answer: answer,
+            elapsed: start.elapsed(),
+        });
+    }
+    return Ok((records, S::warnings(&parsed)));
+}
+
+pub type SolveFn = fn(u32, &str, &[u32]) -> Result<(Vec<Record>, Vec<Warning>), InputError>;
+pub type BenchFn = fn(&str, usize) -> Result<Vec<Timing>, InputError>;
+// extra subcommands of a day, e.g. `aoc 8 debug`: (tool name, arguments,
+// puzzle input, output format of --format)
+pub type ToolFn = fn(&str, &[String], &Source, Format) -> Result<(), String>;
+
+// Type erased entry of the day registry
+pub struct Day {
+    pub day: u32,
+    pub solve: SolveFn,
+    pub bench: BenchFn,
+    pub tool: Option<ToolFn>,
+}
+
+pub const fn day<S: Solver>(day: u32) -> Day {
+    return Day {
+        day: day,
+        solve: solve::<S>,
+        bench: bench::<S>,
+        tool: None,
+    };
+}
+
+impl Day {
+    pub const fn with_tool(self, tool: ToolFn) -> Day {
+        return Day {
+            tool: Some(tool),
+            ..self
+        };
+    }
+}